@@ -1,15 +1,432 @@
-use derive_utils::quick_derive;
 use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Expr, ExprLit, Fields,
+    Ident, Lit, LitStr,
+};
 
-#[proc_macro_derive(IntoApiError)]
-pub fn derive_iterator(input: TokenStream) -> TokenStream {
-    quick_derive! {
-        input,
-        // trait path
-        ::http_api_problem::IntoApiError,
-        // trait definition
-        pub trait IntoApiError {
-            fn into_api_error(self) -> ::http_api_problem::ApiError;
+/// Derives `IntoApiError`.
+///
+/// Structs, enum variants and fields can be annotated with `#[api_error(...)]`
+/// to build the `ApiError`:
+///
+/// * `status = 404` (or any expression converting into a `StatusCode`)
+/// * `title = "..."`, `message = "..."`, `type_url = "..."`, `instance = "..."`
+///   where the strings may interpolate fields like `"{id} not found"` or `"{0}"`
+/// * `#[api_error(field)]` or `#[api_error(field = "name")]` on a field adds it
+///   as a field of the `ApiError`
+/// * `#[api_error(source)]` on a field makes it the source of the `ApiError`
+/// * `#[api_error(transparent)]` on a struct or variant with a single field
+///   forwards to the `IntoApiError` implementation of that field
+///
+/// An attribute on an enum provides defaults for all of its variants.
+/// Variants without any attributes use these defaults.
+///
+/// Structs without any attributes and variants of enums without any attributes
+/// must have a single field which implements `IntoApiError` itself.
+#[proc_macro_derive(IntoApiError, attributes(api_error))]
+pub fn derive_into_api_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let container = ErrorAttrs::parse(&input.attrs)?;
+
+    let arms = match &input.data {
+        Data::Enum(_) if container.transparent => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`transparent` is only allowed on structs and enum variants",
+            ))
+        }
+        Data::Struct(data) => {
+            let arm = expand_arm(
+                quote!(Self),
+                &data.fields,
+                container,
+                &ErrorAttrs::default(),
+            )?;
+            vec![arm]
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::with_capacity(data.variants.len());
+            for variant in data.variants.iter() {
+                let variant_ident = &variant.ident;
+                let own = ErrorAttrs::parse(&variant.attrs)?;
+                arms.push(expand_arm(
+                    quote!(Self::#variant_ident),
+                    &variant.fields,
+                    own,
+                    &container,
+                )?);
+            }
+            arms
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "IntoApiError can not be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::http_api_problem::IntoApiError for #ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn into_api_error(self) -> ::http_api_problem::ApiError {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+/// The values given with `#[api_error(...)]` on a struct, enum or variant
+#[derive(Default, Clone)]
+struct ErrorAttrs {
+    present: bool,
+    transparent: bool,
+    status: Option<Expr>,
+    title: Option<LitStr>,
+    message: Option<LitStr>,
+    type_url: Option<LitStr>,
+    instance: Option<LitStr>,
+}
+
+impl ErrorAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = ErrorAttrs::default();
+        let mut transparent_span = None;
+
+        for attr in attrs.iter().filter(|a| a.path().is_ident("api_error")) {
+            parsed.present = true;
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("transparent") {
+                    parsed.transparent = true;
+                    transparent_span = Some(meta.path.span());
+                } else if meta.path.is_ident("status") {
+                    let status: Expr = meta.value()?.parse()?;
+                    check_status(&status)?;
+                    parsed.status = Some(status);
+                } else if meta.path.is_ident("title") {
+                    parsed.title = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("message") {
+                    parsed.message = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("type_url") {
+                    parsed.type_url = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("instance") {
+                    parsed.instance = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error(
+                        "expected one of `transparent`, `status`, `title`, `message`, `type_url` or `instance`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+
+        if let (Some(span), true) = (transparent_span, parsed.has_values()) {
+            return Err(syn::Error::new(
+                span,
+                "`transparent` can not be combined with other attributes",
+            ));
+        }
+
+        Ok(parsed)
+    }
+
+    fn has_values(&self) -> bool {
+        self.status.is_some()
+            || self.title.is_some()
+            || self.message.is_some()
+            || self.type_url.is_some()
+            || self.instance.is_some()
+    }
+
+    /// Values not set on `self` are taken from `defaults`
+    ///
+    /// `transparent` is never taken from `defaults`.
+    fn or(self, defaults: &ErrorAttrs) -> Self {
+        let defaults = defaults.clone();
+        ErrorAttrs {
+            present: self.present || defaults.present,
+            transparent: self.transparent,
+            status: self.status.or(defaults.status),
+            title: self.title.or(defaults.title),
+            message: self.message.or(defaults.message),
+            type_url: self.type_url.or(defaults.type_url),
+            instance: self.instance.or(defaults.instance),
+        }
+    }
+}
+
+fn check_status(status: &Expr) -> syn::Result<()> {
+    if let Expr::Lit(ExprLit {
+        lit: Lit::Int(lit), ..
+    }) = status
+    {
+        let code: u16 = lit.base10_parse().unwrap_or(0);
+        if !(100..=999).contains(&code) {
+            return Err(syn::Error::new(
+                lit.span(),
+                "the status code must be within 100 and 999",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The values given with `#[api_error(...)]` on a field
+#[derive(Default)]
+struct FieldAttrs {
+    field: Option<Option<LitStr>>,
+    source: bool,
+}
+
+impl FieldAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = FieldAttrs::default();
+
+        for attr in attrs.iter().filter(|a| a.path().is_ident("api_error")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("field") {
+                    if meta.input.peek(syn::Token![=]) {
+                        parsed.field = Some(Some(meta.value()?.parse()?));
+                    } else {
+                        parsed.field = Some(None);
+                    }
+                } else if meta.path.is_ident("source") {
+                    parsed.source = true;
+                } else {
+                    return Err(meta.error("expected `field` or `source`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(parsed)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.field.is_none() && !self.source
+    }
+}
+
+struct Binding {
+    /// The name used for interpolation, e.g. `id` or `0`
+    name: String,
+    /// The local variable the field is bound to
+    ident: Ident,
+    attrs: FieldAttrs,
+    span: Span,
+}
+
+fn expand_arm(
+    path: TokenStream2,
+    fields: &Fields,
+    own: ErrorAttrs,
+    defaults: &ErrorAttrs,
+) -> syn::Result<TokenStream2> {
+    let mut bindings = Vec::with_capacity(fields.len());
+    for (idx, field) in fields.iter().enumerate() {
+        let (name, ident) = match field.ident.as_ref() {
+            Some(ident) => (ident.to_string(), ident.clone()),
+            None => (idx.to_string(), format_ident!("__field{}", idx)),
+        };
+        bindings.push(Binding {
+            name,
+            ident,
+            attrs: FieldAttrs::parse(&field.attrs)?,
+            span: field.span(),
+        });
+    }
+
+    let idents = bindings.iter().map(|b| &b.ident);
+    let pattern = match fields {
+        Fields::Named(_) => quote!(#path { #(#idents),* }),
+        Fields::Unnamed(_) => quote!(#path ( #(#idents),* )),
+        Fields::Unit => quote!(#path),
+    };
+
+    let has_field_attrs = bindings.iter().any(|b| !b.attrs.is_empty());
+
+    if own.transparent {
+        return match bindings.as_slice() {
+            [binding] if !has_field_attrs => {
+                let ident = &binding.ident;
+                Ok(quote! {
+                    #pattern => ::http_api_problem::IntoApiError::into_api_error(#ident),
+                })
+            }
+            [binding] => Err(syn::Error::new(
+                binding.span,
+                "the field of a `transparent` struct or variant can not have attributes",
+            )),
+            _ => Err(syn::Error::new(
+                path_span(&path),
+                "`transparent` requires exactly one field",
+            )),
+        };
+    }
+
+    // Without any attributes we fall back to the attributes of the enum
+    // or forward to the single field if there are none
+    if !own.present && !has_field_attrs {
+        match bindings.as_slice() {
+            _ if defaults.present => {}
+            [binding] => {
+                let ident = &binding.ident;
+                return Ok(quote! {
+                    #pattern => ::http_api_problem::IntoApiError::into_api_error(#ident),
+                });
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    path_span(&path),
+                    "expected `#[api_error(status = ...)]` or a single field implementing `IntoApiError`",
+                ))
+            }
+        }
+    }
+
+    let attrs = own.or(defaults);
+
+    let status = match attrs.status.as_ref() {
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Int(code),
+            ..
+        })) => quote! {
+            ::http_api_problem::StatusCode::from_u16(#code).expect("a valid status code")
+        },
+        Some(status) => quote!(#status),
+        None => {
+            return Err(syn::Error::new(
+                path_span(&path),
+                "missing `#[api_error(status = ...)]`",
+            ))
+        }
+    };
+
+    let mut steps = Vec::new();
+
+    if let Some(title) = attrs.title.as_ref() {
+        let title = interpolate(title, &bindings)?;
+        steps.push(quote!(__builder = __builder.title(#title);));
+    }
+    if let Some(message) = attrs.message.as_ref() {
+        let message = interpolate(message, &bindings)?;
+        steps.push(quote!(__builder = __builder.message(#message);));
+    }
+    if let Some(type_url) = attrs.type_url.as_ref() {
+        let type_url = interpolate(type_url, &bindings)?;
+        steps.push(quote!(__builder = __builder.type_url(#type_url);));
+    }
+    if let Some(instance) = attrs.instance.as_ref() {
+        let instance = interpolate(instance, &bindings)?;
+        steps.push(quote!(__builder = __builder.instance(#instance);));
+    }
+
+    for binding in bindings.iter() {
+        let ident = &binding.ident;
+        match binding.attrs.field.as_ref() {
+            Some(Some(name)) => steps.push(quote!(__builder = __builder.field(#name, &#ident);)),
+            Some(None) if binding.name.parse::<usize>().is_ok() => {
+                return Err(syn::Error::new(
+                    binding.span,
+                    "unnamed fields require a name: `#[api_error(field = \"name\")]`",
+                ))
+            }
+            Some(None) => {
+                let name = &binding.name;
+                steps.push(quote!(__builder = __builder.field(#name, &#ident);))
+            }
+            None => {}
+        }
+    }
+
+    let mut sources = bindings.iter().filter(|b| b.attrs.source);
+    if let Some(binding) = sources.next() {
+        if let Some(other) = sources.next() {
+            return Err(syn::Error::new(
+                other.span,
+                "only one field can be the `source`",
+            ));
+        }
+        let ident = &binding.ident;
+        steps.push(quote!(__builder = __builder.source_in_a_box(#ident);));
+    }
+
+    Ok(quote! {
+        #pattern => {
+            let mut __builder = ::http_api_problem::ApiError::builder(#status);
+            #(#steps)*
+            __builder.finish()
+        }
+    })
+}
+
+fn path_span(path: &TokenStream2) -> Span {
+    path.clone()
+        .into_iter()
+        .last()
+        .map(|t| t.span())
+        .unwrap_or_else(Span::call_site)
+}
+
+/// Turns a string with placeholders like `{id}` or `{0:?}` into a `format!` invocation
+fn interpolate(lit: &LitStr, bindings: &[Binding]) -> syn::Result<TokenStream2> {
+    let value = lit.value();
+    let mut rewritten = String::with_capacity(value.len());
+    let mut args: Vec<&Ident> = Vec::new();
+
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        rewritten.push(c);
+        match c {
+            '{' if chars.peek() == Some(&'{') => rewritten.push(chars.next().unwrap()),
+            '}' if chars.peek() == Some(&'}') => rewritten.push(chars.next().unwrap()),
+            '{' => {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '}' || c == ':' {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                let name = name.trim();
+
+                if name.is_empty() {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "placeholders must name a field, e.g. `{id}` or `{0}`",
+                    ));
+                }
+
+                let binding = bindings.iter().find(|b| b.name == name).ok_or_else(|| {
+                    syn::Error::new(lit.span(), format!("there is no field named `{}`", name))
+                })?;
+
+                rewritten.push_str(&binding.ident.to_string());
+                if !args.contains(&&binding.ident) {
+                    args.push(&binding.ident);
+                }
+            }
+            _ => {}
         }
     }
+
+    let rewritten = LitStr::new(&rewritten, lit.span());
+
+    Ok(quote!(::std::format!(#rewritten #(, #args = #args)*)))
 }
@@ -0,0 +1,173 @@
+//! Problems for the errors of the built-in extractors of [actix-web](https://docs.rs/actix-web).
+//!
+//! Requires the `actix-web` feature
+use actix_web::error::{
+    InternalError, JsonPayloadError, PathError, QueryPayloadError, UrlencodedError,
+};
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+
+use super::*;
+
+impl From<&JsonPayloadError> for HttpApiProblem {
+    /// Payloads which are too large become `413 - Payload Too Large`,
+    /// a wrong content type becomes `415 - Unsupported Media Type` and
+    /// JSON which does not match the expected type becomes `422 - Unprocessable Entity`.
+    /// Malformed JSON becomes `400 - Bad Request`.
+    fn from(error: &JsonPayloadError) -> Self {
+        let status = match error {
+            JsonPayloadError::OverflowKnownLength { .. } | JsonPayloadError::Overflow { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            JsonPayloadError::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            JsonPayloadError::Deserialize(err) if err.is_data() => StatusCode::UNPROCESSABLE_ENTITY,
+            err => actix_status(err),
+        };
+
+        HttpApiProblem::with_title_and_type(status).detail(error.to_string())
+    }
+}
+
+impl From<&PathError> for HttpApiProblem {
+    /// Path parameters which can not be deserialized become `400 - Bad Request`.
+    fn from(error: &PathError) -> Self {
+        HttpApiProblem::with_title_and_type(StatusCode::BAD_REQUEST).detail(error.to_string())
+    }
+}
+
+impl From<&QueryPayloadError> for HttpApiProblem {
+    /// Query strings which can not be deserialized become `400 - Bad Request`.
+    fn from(error: &QueryPayloadError) -> Self {
+        HttpApiProblem::with_title_and_type(StatusCode::BAD_REQUEST).detail(error.to_string())
+    }
+}
+
+impl From<&UrlencodedError> for HttpApiProblem {
+    /// Payloads which are too large become `413 - Payload Too Large`,
+    /// a wrong content type becomes `415 - Unsupported Media Type` and
+    /// forms which do not match the expected type become `422 - Unprocessable Entity`.
+    fn from(error: &UrlencodedError) -> Self {
+        let status = match error {
+            UrlencodedError::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            UrlencodedError::ContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            UrlencodedError::Parse(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            err => actix_status(err),
+        };
+
+        HttpApiProblem::with_title_and_type(status).detail(error.to_string())
+    }
+}
+
+macro_rules! from_owned_error {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for HttpApiProblem {
+                fn from(error: $error) -> Self {
+                    HttpApiProblem::from(&error)
+                }
+            }
+
+            #[cfg(feature = "api-error")]
+            impl From<$error> for ApiError {
+                /// Creates an error with the status and message of the
+                /// corresponding [HttpApiProblem] and the error as the `source`.
+                fn from(error: $error) -> Self {
+                    let problem = HttpApiProblem::from(&error);
+                    let mut builder = ApiError::builder(problem.status_or_internal_server_error());
+                    if let Some(detail) = problem.detail {
+                        builder = builder.message(detail);
+                    }
+                    builder.source(error).finish()
+                }
+            }
+        )*
+    };
+}
+
+from_owned_error!(
+    JsonPayloadError,
+    PathError,
+    QueryPayloadError,
+    UrlencodedError
+);
+
+/// An error handler for actix-web's `JsonConfig` which responds with a problem.
+///
+/// The format of the problem is negotiated with the `Accept` header
+/// of the request. See [ProblemFormat::negotiate].
+///
+/// Requires the `actix-web` feature
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use actix_web_crate as actix_web;
+/// use actix_web::{web, App};
+/// use http_api_problem::*;
+///
+/// let app = App::new()
+///     .app_data(web::JsonConfig::default().error_handler(actix_json_error_handler))
+///     .app_data(web::PathConfig::default().error_handler(actix_path_error_handler))
+///     .app_data(web::QueryConfig::default().error_handler(actix_query_error_handler))
+///     .app_data(web::FormConfig::default().error_handler(actix_form_error_handler));
+/// ```
+pub fn actix_json_error_handler(error: JsonPayloadError, req: &HttpRequest) -> actix_web::Error {
+    problem_error(HttpApiProblem::from(&error), error, req)
+}
+
+/// An error handler for actix-web's `PathConfig` which responds with a problem.
+///
+/// See [actix_json_error_handler]
+///
+/// Requires the `actix-web` feature
+pub fn actix_path_error_handler(error: PathError, req: &HttpRequest) -> actix_web::Error {
+    problem_error(HttpApiProblem::from(&error), error, req)
+}
+
+/// An error handler for actix-web's `QueryConfig` which responds with a problem.
+///
+/// See [actix_json_error_handler]
+///
+/// Requires the `actix-web` feature
+pub fn actix_query_error_handler(error: QueryPayloadError, req: &HttpRequest) -> actix_web::Error {
+    problem_error(HttpApiProblem::from(&error), error, req)
+}
+
+/// An error handler for actix-web's `FormConfig` which responds with a problem.
+///
+/// See [actix_json_error_handler]
+///
+/// Requires the `actix-web` feature
+pub fn actix_form_error_handler(error: UrlencodedError, req: &HttpRequest) -> actix_web::Error {
+    problem_error(HttpApiProblem::from(&error), error, req)
+}
+
+/// Keeps `error` as the cause but responds with `problem`
+fn problem_error<E>(problem: HttpApiProblem, error: E, req: &HttpRequest) -> actix_web::Error
+where
+    E: fmt::Debug + fmt::Display + 'static,
+{
+    InternalError::from_response(error, negotiated_response(&problem, req)).into()
+}
+
+fn negotiated_response(problem: &HttpApiProblem, req: &HttpRequest) -> HttpResponse {
+    let accept = req
+        .headers()
+        .get_all(header::ACCEPT)
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(",");
+    let format = ProblemFormat::negotiate(&accept);
+
+    let status =
+        actix_web::http::StatusCode::from_u16(problem.status_code_or_internal_server_error())
+            .unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+
+    HttpResponse::build(status)
+        .append_header((header::CONTENT_TYPE, format.content_type()))
+        .body(problem.format_bytes(format))
+}
+
+fn actix_status<E: ResponseError>(error: &E) -> StatusCode {
+    StatusCode::from_u16(error.status_code().as_u16()).unwrap_or(StatusCode::BAD_REQUEST)
+}
@@ -294,8 +294,8 @@ impl ApiError {
 
     /// Set a URL that points to a detailed description of the error.
     ///
-    /// If not set it will most probably become `httpstatus.es.com/XXX` when
-    /// the problem response is generated.
+    /// If not set it will be derived from the status code according to the
+    /// [DefaultTypeUrl] in effect when the problem response is generated.
     pub fn set_type_url<T: Display>(&mut self, type_url: T) {
         self.type_url = Some(type_url.to_string())
     }
@@ -394,13 +394,18 @@ impl ApiError {
     pub fn to_http_api_problem(&self) -> HttpApiProblem {
         let mut problem = HttpApiProblem::with_title_and_type(self.status);
 
-        problem.title.clone_from(&self.title);
+        if let Some(title) = self.title.as_ref() {
+            problem.title = Some(title.to_owned());
+        }
 
         if let Some(message) = self.detail_message() {
             problem.detail = Some(message.into())
         }
 
-        problem.type_url.clone_from(&self.type_url);
+        if let Some(type_url) = self.type_url.as_ref() {
+            problem.type_url = Some(type_url.to_owned())
+        }
+
         problem.instance.clone_from(&self.instance);
 
         if self.status != StatusCode::UNAUTHORIZED {
@@ -447,7 +452,7 @@ impl ApiError {
     /// If there is a message it will be the message otherwise the source error stringified
     ///
     /// If none is present, `None` is returned
-    pub fn detail_message(&self) -> Option<Cow<str>> {
+    pub fn detail_message(&self) -> Option<Cow<'_, str>> {
         if let Some(message) = self.message.as_ref() {
             return Some(Cow::Borrowed(message));
         }
@@ -486,6 +491,15 @@ impl ApiError {
         problem.into()
     }
 
+    /// Creates a [poem] response containing a problem JSON.
+    ///
+    /// Requires the `poem` feature
+    #[cfg(feature = "poem")]
+    pub fn into_poem_response(self) -> poem::Response {
+        let problem = self.into_http_api_problem();
+        problem.to_poem_response()
+    }
+
     /// Creates a [salvo] response containing a problem JSON.
     ///
     /// Requires the `salvo` feature
@@ -503,6 +517,15 @@ impl ApiError {
         let problem = self.into_http_api_problem();
         problem.to_tide_response()
     }
+
+    /// Creates a [warp] response containing a problem JSON.
+    ///
+    /// Requires the `warp` feature
+    #[cfg(feature = "warp")]
+    pub fn into_warp_response(self) -> warp::reply::Response {
+        let problem = self.into_http_api_problem();
+        problem.to_warp_response()
+    }
 }
 
 impl Error for ApiError {
@@ -567,10 +590,169 @@ impl From<std::convert::Infallible> for ApiError {
     }
 }
 
+/// Types which can be turned into an [ApiError]
+///
+/// The trait can be derived. Structs and enum variants are annotated with
+/// `#[api_error(...)]` to describe the [ApiError] to build:
+///
+/// * `status`: The [StatusCode] as a number or an expression which converts into a [StatusCode]
+/// * `title`, `message`, `type_url`, `instance`: Strings which may contain
+///   placeholders for fields of the struct or variant like `{id}` or `{0}`
+///
+/// Fields can be annotated with `#[api_error(field)]`, `#[api_error(field = "name")]`
+/// to add them as a field to the [ApiError] and with `#[api_error(source)]` to
+/// make them the source of the [ApiError].
+///
+/// An `#[api_error(...)]` attribute on an enum sets the defaults for all
+/// of its variants. Variants without any attributes use the attributes of the enum.
+/// If the enum has no attributes, variants without any attributes must have a single field
+/// and forward to the [IntoApiError] implementation of that field.
+/// `#[api_error(transparent)]` forwards to the single field of a struct or
+/// variant regardless of the attributes of the enum.
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// #[derive(Debug)]
+/// struct DbError;
+///
+/// impl std::fmt::Display for DbError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///         write!(f, "connection lost")
+///     }
+/// }
+///
+/// impl std::error::Error for DbError {}
+///
+/// #[derive(IntoApiError)]
+/// #[api_error(type_url = "https://example.com/probs/accounts")]
+/// enum AccountError {
+///     #[api_error(status = 404, title = "Unknown account", message = "{id} not found")]
+///     NotFound {
+///         #[api_error(field)]
+///         id: u32,
+///     },
+///     #[api_error(status = StatusCode::SERVICE_UNAVAILABLE)]
+///     Unavailable(#[api_error(source)] DbError),
+/// }
+///
+/// let error = AccountError::NotFound { id: 42 }.into_api_error();
+///
+/// assert_eq!(error.status(), StatusCode::NOT_FOUND);
+/// assert_eq!(error.title(), Some("Unknown account"));
+/// assert_eq!(error.message(), Some("42 not found"));
+/// assert_eq!(error.type_url(), Some("https://example.com/probs/accounts"));
+/// assert_eq!(error.fields().get("id"), Some(&serde_json::json!(42)));
+///
+/// let error: ApiError = AccountError::Unavailable(DbError).into();
+///
+/// assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+/// assert_eq!(error.detail_message().as_deref(), Some("connection lost"));
+/// ```
+///
+/// Forwarding to the fields:
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// #[derive(IntoApiError)]
+/// #[api_error(status = 400, message = "invalid input: {0}")]
+/// struct InvalidInput(String);
+///
+/// #[derive(IntoApiError)]
+/// #[api_error(status = 504, message = "timed out after {millis} ms")]
+/// struct Timeout {
+///     #[api_error(field = "after_ms")]
+///     millis: u64,
+///     #[api_error(source)]
+///     source: std::io::Error,
+/// }
+///
+/// #[derive(IntoApiError)]
+/// enum HandlerError {
+///     Input(InvalidInput),
+///     Timeout(Timeout),
+/// }
+///
+/// let error = HandlerError::Input(InvalidInput("foo".into())).into_api_error();
+///
+/// assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+/// assert_eq!(error.message(), Some("invalid input: foo"));
+///
+/// let source = std::io::Error::new(std::io::ErrorKind::TimedOut, "timeout");
+/// let error = HandlerError::Timeout(Timeout { millis: 500, source }).into_api_error();
+///
+/// assert_eq!(error.status(), StatusCode::GATEWAY_TIMEOUT);
+/// assert_eq!(error.message(), Some("timed out after 500 ms"));
+/// assert_eq!(error.fields().get("after_ms"), Some(&serde_json::json!(500)));
+/// assert!(std::error::Error::source(&error).is_some());
+/// ```
+///
+/// Falling back to the attributes of the enum:
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// #[derive(IntoApiError)]
+/// #[api_error(status = 500, title = "Storage failure")]
+/// enum StorageError {
+///     Corrupted,
+///     Full { used: u64, available: u64 },
+///     Io(std::io::Error),
+///     #[api_error(status = 503)]
+///     Offline,
+///     #[api_error(transparent)]
+///     Problem(HttpApiProblem),
+/// }
+///
+/// let error = StorageError::Corrupted.into_api_error();
+///
+/// assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
+/// assert_eq!(error.title(), Some("Storage failure"));
+///
+/// let error = StorageError::Full { used: 10, available: 0 }.into_api_error();
+///
+/// assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
+///
+/// let io = std::io::Error::new(std::io::ErrorKind::Other, "disk gone");
+/// let error = StorageError::Io(io).into_api_error();
+///
+/// assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
+///
+/// let error = StorageError::Offline.into_api_error();
+///
+/// assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+/// assert_eq!(error.title(), Some("Storage failure"));
+///
+/// let error = StorageError::Problem(HttpApiProblem::new(StatusCode::CONFLICT)).into_api_error();
+///
+/// assert_eq!(error.status(), StatusCode::CONFLICT);
+/// ```
 pub trait IntoApiError {
     fn into_api_error(self) -> ApiError;
 }
 
+impl IntoApiError for HttpApiProblem {
+    /// Creates an [ApiError] from the [HttpApiProblem]
+    ///
+    /// The `detail` becomes the `message` of the [ApiError].
+    /// If no status is set `500 - Internal Server Error` is the default.
+    fn into_api_error(self) -> ApiError {
+        ApiError {
+            status: self.status_or_internal_server_error(),
+            title: self.title,
+            message: self.detail,
+            type_url: self.type_url,
+            instance: self.instance,
+            fields: self.additional_fields,
+            extensions: Extensions::default(),
+            source: None,
+        }
+    }
+}
+
 impl<T: IntoApiError> From<T> for ApiError {
     fn from(t: T) -> ApiError {
         t.into_api_error()
@@ -602,8 +784,9 @@ impl From<ApiError> for axum_core::response::Response {
 
 #[cfg(feature = "axum")]
 impl axum_core::response::IntoResponse for ApiError {
+    /// See the implementation for [HttpApiProblem]
     fn into_response(self) -> axum_core::response::Response {
-        self.into()
+        self.into_http_api_problem().into_response()
     }
 }
 
@@ -642,6 +825,45 @@ impl actix_web::error::ResponseError for ApiError {
 #[cfg(feature = "warp")]
 impl warp::reject::Reject for ApiError {}
 
+#[cfg(feature = "warp")]
+impl From<ApiError> for warp::reply::Response {
+    fn from(error: ApiError) -> warp::reply::Response {
+        error.into_warp_response()
+    }
+}
+
+#[cfg(feature = "warp")]
+impl warp::Reply for ApiError {
+    fn into_response(self) -> warp::reply::Response {
+        self.into_warp_response()
+    }
+}
+
+#[cfg(feature = "poem")]
+impl From<ApiError> for poem::Response {
+    fn from(error: ApiError) -> poem::Response {
+        error.into_poem_response()
+    }
+}
+
+#[cfg(feature = "poem")]
+impl poem::IntoResponse for ApiError {
+    fn into_response(self) -> poem::Response {
+        self.into_poem_response()
+    }
+}
+
+#[cfg(feature = "poem")]
+impl poem::error::ResponseError for ApiError {
+    fn status(&self) -> poem::http::StatusCode {
+        self.status
+    }
+
+    fn as_response(&self) -> poem::Response {
+        self.to_http_api_problem().to_poem_response()
+    }
+}
+
 #[cfg(feature = "salvo")]
 impl From<salvo::Error> for ApiError {
     fn from(error: salvo::Error) -> Self {
@@ -0,0 +1,136 @@
+//! Problems for the rejections of the built-in extractors of [axum](https://docs.rs/axum).
+//!
+//! The rejections are `application/problem+json` responses. Add the
+//! `ProblemLayer` of the `tower` feature to negotiate their format.
+//!
+//! Requires the `axum` feature
+use axum::extract::rejection::{FormRejection, JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{FromRequest, FromRequestParts, Path, Query, Request};
+use axum::{Form, Json};
+use http::request::Parts;
+
+use super::*;
+
+macro_rules! from_rejection {
+    ($($rejection:ty),*) => {
+        $(
+            impl From<$rejection> for HttpApiProblem {
+                /// Creates a problem with the status of the rejection
+                /// and its message as the `detail`.
+                fn from(rejection: $rejection) -> Self {
+                    HttpApiProblem::with_title_and_type(rejection.status())
+                        .detail(rejection.body_text())
+                }
+            }
+
+            #[cfg(feature = "api-error")]
+            impl From<$rejection> for ApiError {
+                /// Creates an error with the status of the rejection, its message
+                /// as the `message` and the rejection as the `source`.
+                fn from(rejection: $rejection) -> Self {
+                    ApiError::builder(rejection.status())
+                        .message(rejection.body_text())
+                        .source(rejection)
+                        .finish()
+                }
+            }
+        )*
+    };
+}
+
+from_rejection!(JsonRejection, PathRejection, QueryRejection, FormRejection);
+
+/// Like axum's `Json` extractor but rejects with an [HttpApiProblem].
+///
+/// Requires the `axum` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct NewUser {
+///     name: String,
+/// }
+///
+/// async fn create_user(
+///     ProblemPath(team): ProblemPath<u32>,
+///     ProblemJson(user): ProblemJson<NewUser>,
+/// ) -> Result<String, HttpApiProblem> {
+///     Ok(format!("{} joined team {}", user.name, team))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProblemJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ProblemJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = HttpApiProblem;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+        Ok(ProblemJson(value))
+    }
+}
+
+/// Like axum's `Path` extractor but rejects with an [HttpApiProblem].
+///
+/// Requires the `axum` feature
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProblemPath<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for ProblemPath<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = HttpApiProblem;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(value) = Path::<T>::from_request_parts(parts, state).await?;
+        Ok(ProblemPath(value))
+    }
+}
+
+/// Like axum's `Query` extractor but rejects with an [HttpApiProblem].
+///
+/// Requires the `axum` feature
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProblemQuery<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for ProblemQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = HttpApiProblem;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state).await?;
+        Ok(ProblemQuery(value))
+    }
+}
+
+/// Like axum's `Form` extractor but rejects with an [HttpApiProblem].
+///
+/// Requires the `axum` feature
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProblemForm<T>(pub T);
+
+impl<T, S> FromRequest<S> for ProblemForm<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = HttpApiProblem;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Form(value) = Form::<T>::from_request(req, state).await?;
+        Ok(ProblemForm(value))
+    }
+}
@@ -0,0 +1,48 @@
+//! The CBOR encoding of problems (`application/problem+cbor`).
+//!
+//! The members are encoded as a CBOR map with the same keys as the JSON format.
+//! The `status` is an integer.
+//!
+//! Decoding goes through the same data model as JSON: extension members become
+//! [serde_json::Value]s. Byte strings, tags and maps with keys which are not
+//! strings can not be decoded and floats which are not finite become `null`.
+//!
+//! Requires the `cbor` feature
+use super::*;
+
+/// The error when decoding a problem from CBOR
+///
+/// Requires the `cbor` feature
+pub type ProblemCborError = ciborium::de::Error<std::io::Error>;
+
+impl HttpApiProblem {
+    /// Serialize to a CBOR `Vec<u8>`
+    ///
+    /// Requires the `cbor` feature
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut cbor = Vec::new();
+        ciborium::into_writer(self, &mut cbor).unwrap();
+        cbor
+    }
+
+    /// Parses a problem from its CBOR encoding.
+    ///
+    /// Requires the `cbor` feature
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// let p = HttpApiProblem::new(StatusCode::FORBIDDEN)
+    ///     .title("You do not have enough credit.")
+    ///     .value("balance", &30);
+    ///
+    /// let parsed = HttpApiProblem::from_cbor(p.to_cbor()).unwrap();
+    ///
+    /// assert_eq!(parsed, p);
+    /// ```
+    pub fn from_cbor<T: AsRef<[u8]>>(cbor: T) -> Result<Self, ProblemCborError> {
+        ciborium::from_reader(cbor.as_ref())
+    }
+}
@@ -0,0 +1,99 @@
+//! Parsing problems from responses received from other services.
+//!
+//! The functions in here never fail. If a response does not
+//! contain a problem, a problem is created from the status code
+//! of the response.
+use http::header::{HeaderMap, CONTENT_TYPE};
+
+use super::*;
+
+impl HttpApiProblem {
+    /// Creates an [HttpApiProblem] from an [http::Response].
+    ///
+    /// If the `Content-Type` of the response is `application/problem+json`
+    /// the body is parsed as an [HttpApiProblem]. Otherwise or if the body can not be parsed
+    /// a problem with the `title` derived from the status of the response is created.
+    ///
+    /// The status of the response is authoritative. If the `status` contained
+    /// in the body is missing or differs from it, it will be replaced by the
+    /// status of the response.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// let response = http::Response::builder()
+    ///     .status(StatusCode::FORBIDDEN)
+    ///     .header("content-type", PROBLEM_JSON_MEDIA_TYPE)
+    ///     .body(r#"{"title": "You do not have enough credit.", "balance": 30}"#)
+    ///     .unwrap();
+    ///
+    /// let p = HttpApiProblem::from_response(&response);
+    ///
+    /// assert_eq!(Some(StatusCode::FORBIDDEN), p.status);
+    /// assert_eq!(Some("You do not have enough credit."), p.title.as_deref());
+    /// assert_eq!(Some(30), p.get_value::<&str, u32>("balance"));
+    ///
+    /// let response = http::Response::builder()
+    ///     .status(StatusCode::BAD_GATEWAY)
+    ///     .header("content-type", "text/html")
+    ///     .body("<h1>Bad Gateway</h1>")
+    ///     .unwrap();
+    ///
+    /// let p = HttpApiProblem::from_response(&response);
+    ///
+    /// assert_eq!(Some(StatusCode::BAD_GATEWAY), p.status);
+    /// assert_eq!(Some("Bad Gateway"), p.title.as_deref());
+    /// ```
+    pub fn from_response<B: AsRef<[u8]>>(response: &http::Response<B>) -> Self {
+        Self::from_response_parts(
+            response.status(),
+            response.headers(),
+            response.body().as_ref(),
+        )
+    }
+
+    /// Creates an [HttpApiProblem] from the parts of a response.
+    ///
+    /// See [HttpApiProblem::from_response]
+    pub fn from_response_parts(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Self {
+        if !is_problem_json_response(headers) {
+            return Self::with_title(status);
+        }
+
+        match serde_json::from_slice::<HttpApiProblem>(body) {
+            Ok(problem) => problem.status(status),
+            Err(_) => Self::with_title(status),
+        }
+    }
+}
+
+/// Returns `true` if the `Content-Type` header is `application/problem+json`.
+///
+/// Parameters like `charset` are ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+/// use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+///
+/// let mut headers = HeaderMap::new();
+/// assert!(!is_problem_json_response(&headers));
+///
+/// headers.insert(CONTENT_TYPE, HeaderValue::from_static("Application/Problem+JSON; charset=utf-8"));
+/// assert!(is_problem_json_response(&headers));
+/// ```
+pub fn is_problem_json_response(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|media_type| {
+            media_type
+                .trim()
+                .eq_ignore_ascii_case(PROBLEM_JSON_MEDIA_TYPE)
+        })
+        .unwrap_or(false)
+}
@@ -0,0 +1,125 @@
+//! The `type` of problems which are created from a [StatusCode] only.
+//!
+//! [HttpApiProblem::with_title_and_type](crate::HttpApiProblem::with_title_and_type)
+//! and the conversion of an `ApiError` without a `type_url` derive the `type`
+//! from the status code. The URI used can be selected process wide via
+//! [set_default_type_url], temporarily for the current thread via
+//! [with_default_type_url] or for a [ProblemTypeRegistry](crate::ProblemTypeRegistry).
+use std::cell::RefCell;
+
+use super::scoped_setting::ScopedSetting;
+use super::*;
+
+thread_local! {
+    static DEFAULT_TYPE_URL_OVERRIDE: RefCell<Option<DefaultTypeUrl>> = const { RefCell::new(None) };
+}
+
+static DEFAULT_TYPE_URL: ScopedSetting<DefaultTypeUrl> =
+    ScopedSetting::new(&DEFAULT_TYPE_URL_OVERRIDE);
+
+/// How the `type` of a problem is derived from a [StatusCode]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DefaultTypeUrl {
+    /// `https://httpstatuses.com/{code}`
+    ///
+    /// This is the default with [RfcMode::Rfc7807]
+    HttpStatuses,
+    /// `about:blank`
+    ///
+    /// This is the default with [RfcMode::Rfc9457]
+    AboutBlank,
+    /// `https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Status/{code}`
+    Mdn,
+    /// The status code appended to the given base URL, e.g.
+    /// `https://docs.example.com/problems/404` for `https://docs.example.com/problems`
+    BaseUrl(String),
+    /// No `type` is set
+    None,
+}
+
+impl DefaultTypeUrl {
+    /// Returns the `type` for the given [StatusCode]
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// let default = DefaultTypeUrl::BaseUrl("https://docs.example.com/problems".to_string());
+    ///
+    /// assert_eq!(
+    ///     Some("https://docs.example.com/problems/404".to_string()),
+    ///     default.type_url(StatusCode::NOT_FOUND)
+    /// );
+    /// assert_eq!(None, DefaultTypeUrl::None.type_url(StatusCode::NOT_FOUND));
+    /// ```
+    pub fn type_url(&self, status: StatusCode) -> Option<String> {
+        let code = status.as_u16();
+        match self {
+            DefaultTypeUrl::HttpStatuses => Some(format!("https://httpstatuses.com/{}", code)),
+            DefaultTypeUrl::AboutBlank => Some(ABOUT_BLANK.to_string()),
+            DefaultTypeUrl::Mdn => Some(format!(
+                "https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Status/{}",
+                code
+            )),
+            DefaultTypeUrl::BaseUrl(base) => {
+                Some(format!("{}/{}", base.trim_end_matches('/'), code))
+            }
+            DefaultTypeUrl::None => None,
+        }
+    }
+}
+
+/// Returns the [DefaultTypeUrl] in effect for the current thread.
+///
+/// This is the one set with [with_default_type_url] if called from within its
+/// closure and the process wide one set with [set_default_type_url] otherwise.
+/// If none was set it depends on the [RfcMode] in effect.
+pub fn default_type_url() -> DefaultTypeUrl {
+    DEFAULT_TYPE_URL.get().unwrap_or_else(|| match rfc_mode() {
+        RfcMode::Rfc7807 => DefaultTypeUrl::HttpStatuses,
+        RfcMode::Rfc9457 => DefaultTypeUrl::AboutBlank,
+    })
+}
+
+/// Sets the process wide [DefaultTypeUrl].
+///
+/// `None` restores the default which depends on the [RfcMode] in effect.
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// set_default_type_url(DefaultTypeUrl::Mdn);
+///
+/// let p = HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND);
+///
+/// assert_eq!(
+///     Some("https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Status/404"),
+///     p.type_url.as_deref()
+/// );
+/// ```
+pub fn set_default_type_url<T: Into<Option<DefaultTypeUrl>>>(default: T) {
+    DEFAULT_TYPE_URL.set(default.into());
+}
+
+/// Runs `f` with `default` in effect for the current thread.
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let p = with_default_type_url(DefaultTypeUrl::None, || {
+///     HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+/// });
+///
+/// assert_eq!(None, p.type_url);
+/// ```
+pub fn with_default_type_url<F, T>(default: DefaultTypeUrl, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    DEFAULT_TYPE_URL.scoped(default, f)
+}
@@ -0,0 +1,226 @@
+//! Rendering problems as HTML for browsers.
+//!
+//! Problems are rendered as HTML when [ProblemFormat::Html] is negotiated.
+//! The [HtmlTemplate] used can be replaced process wide via [set_html_template]
+//! or temporarily for the current thread via [with_html_template].
+//! [DefaultHtmlTemplate] is used if none was set.
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use super::scoped_setting::ScopedSetting;
+use super::*;
+
+thread_local! {
+    static HTML_TEMPLATE_OVERRIDE: RefCell<Option<Arc<dyn HtmlTemplate>>> = const { RefCell::new(None) };
+}
+
+static HTML_TEMPLATE: ScopedSetting<Arc<dyn HtmlTemplate>> =
+    ScopedSetting::new(&HTML_TEMPLATE_OVERRIDE);
+
+/// Renders an [HttpApiProblem] as an HTML page
+///
+/// Implementations must escape all values taken from the problem,
+/// e.g. with [escape_html].
+///
+/// It is implemented for closures taking an [HttpApiProblem] and returning a [String].
+pub trait HtmlTemplate: Send + Sync {
+    /// Returns the HTML page for the problem
+    fn render(&self, problem: &HttpApiProblem) -> String;
+}
+
+impl<F> HtmlTemplate for F
+where
+    F: Fn(&HttpApiProblem) -> String + Send + Sync,
+{
+    fn render(&self, problem: &HttpApiProblem) -> String {
+        self(problem)
+    }
+}
+
+/// A minimal HTML page
+///
+/// It shows the `title`, `status` and `detail` of the problem, links the `type`
+/// if it is an HTTP(S) URL and lists the `instance` and the extension members
+/// in a table. All values are escaped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultHtmlTemplate;
+
+impl HtmlTemplate for DefaultHtmlTemplate {
+    fn render(&self, problem: &HttpApiProblem) -> String {
+        let title = escape_html(
+            problem
+                .title
+                .as_deref()
+                .or_else(|| problem.status.and_then(|s| s.canonical_reason()))
+                .unwrap_or("Error"),
+        );
+
+        let mut html = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+        html.push_str("<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n", title));
+        html.push_str(
+            "<style>body{font-family:sans-serif;margin:2em}\
+             th,td{text-align:left;padding:.2em 1em .2em 0;vertical-align:top}</style>\n",
+        );
+        html.push_str("</head>\n<body>\n");
+        html.push_str(&format!("<h1>{}</h1>\n", title));
+
+        if let Some(status) = problem.status {
+            html.push_str(&format!(
+                "<p class=\"status\">{}</p>\n",
+                escape_html(&status.to_string())
+            ));
+        }
+        if let Some(detail) = problem.detail.as_deref() {
+            html.push_str(&format!(
+                "<p class=\"detail\">{}</p>\n",
+                escape_html(detail)
+            ));
+        }
+
+        let mut rows = Vec::new();
+        if let Some(type_url) = problem.type_url.as_deref() {
+            rows.push(("type".to_string(), link(type_url)));
+        }
+        if let Some(instance) = problem.instance.as_deref() {
+            rows.push(("instance".to_string(), escape_html(instance)));
+        }
+
+        let mut members = problem
+            .additional_fields()
+            .iter()
+            .filter(|(name, _)| !is_member_name(name))
+            .collect::<Vec<_>>();
+        members.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, value) in members {
+            let value = match value {
+                Value::String(s) => escape_html(s),
+                other => escape_html(&other.to_string()),
+            };
+            rows.push((escape_html(name), value));
+        }
+
+        if !rows.is_empty() {
+            html.push_str("<table>\n");
+            for (name, value) in rows {
+                html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", name, value));
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// Links the URL if it is safe to do so
+fn link(url: &str) -> String {
+    let escaped = escape_html(url);
+    let scheme = url.split_once(':').map(|(scheme, _)| scheme);
+    match scheme {
+        Some(s) if s.eq_ignore_ascii_case("http") || s.eq_ignore_ascii_case("https") => {
+            format!("<a href=\"{0}\">{0}</a>", escaped)
+        }
+        _ => escaped,
+    }
+}
+
+/// Escapes text for the use in HTML elements and attribute values
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// assert_eq!(escape_html(r#"<a href="x">&</a>"#), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+/// ```
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns the [HtmlTemplate] in effect for the current thread.
+///
+/// This is the one set with [with_html_template] if called from within its
+/// closure, the process wide one set with [set_html_template] otherwise and
+/// [DefaultHtmlTemplate] if none was set.
+pub fn html_template() -> Arc<dyn HtmlTemplate> {
+    HTML_TEMPLATE
+        .get()
+        .unwrap_or_else(|| Arc::new(DefaultHtmlTemplate))
+}
+
+/// Sets the process wide [HtmlTemplate].
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// set_html_template(|problem: &HttpApiProblem| {
+///     format!("<h1>Oops: {}</h1>", escape_html(&problem.to_string()))
+/// });
+///
+/// let p = HttpApiProblem::new(StatusCode::NOT_FOUND);
+///
+/// assert_eq!(p.html_string(), "<h1>Oops: 404 Not Found</h1>");
+/// ```
+pub fn set_html_template<T: HtmlTemplate + 'static>(template: T) {
+    HTML_TEMPLATE.set(Some(Arc::new(template)));
+}
+
+/// Runs `f` with `template` in effect for the current thread.
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let html = with_html_template(
+///     |_: &HttpApiProblem| "<h1>Sorry</h1>".to_string(),
+///     || HttpApiProblem::new(StatusCode::NOT_FOUND).html_string(),
+/// );
+///
+/// assert_eq!(html, "<h1>Sorry</h1>");
+/// ```
+pub fn with_html_template<T, F, R>(template: T, f: F) -> R
+where
+    T: HtmlTemplate + 'static,
+    F: FnOnce() -> R,
+{
+    HTML_TEMPLATE.scoped(Arc::new(template), f)
+}
+
+impl HttpApiProblem {
+    /// Renders an HTML page with the [HtmlTemplate] in effect
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// let p = HttpApiProblem::new(StatusCode::FORBIDDEN)
+    ///     .title("<Not enough credit>")
+    ///     .type_url("https://example.com/probs/out-of-credit")
+    ///     .value("balance", &30);
+    ///
+    /// let html = with_html_template(DefaultHtmlTemplate, || p.html_string());
+    ///
+    /// assert!(html.contains("<h1>&lt;Not enough credit&gt;</h1>"));
+    /// assert!(html.contains(r#"<a href="https://example.com/probs/out-of-credit">"#));
+    /// assert!(html.contains("<tr><th>balance</th><td>30</td></tr>"));
+    /// ```
+    pub fn html_string(&self) -> String {
+        html_template().render(self)
+    }
+}
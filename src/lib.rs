@@ -8,7 +8,8 @@
 //! [![license-apache](http://img.shields.io/badge/license-APACHE-blue.svg)](https://github.com/chridou/http-api-problem/blob/master/LICENSE-APACHE)
 //!
 //! A library to create HTTP response content for APIs based on
-//! [RFC7807](https://tools.ietf.org/html/rfc7807).
+//! [RFC7807](https://tools.ietf.org/html/rfc7807) and
+//! [RFC9457](https://www.rfc-editor.org/rfc/rfc9457).
 //!
 //! ## Usage
 //!
@@ -26,6 +27,44 @@
 //! [HttpApiProblem] implements [Serialize] and [Deserialize] for
 //! [HttpApiProblem].
 //!
+//! The `status` may also be a string containing an integer. An invalid `status`
+//! is available via [HttpApiProblem::raw_status] unless
+//! [set_status_deserialization] with [StatusDeserialization::Strict] makes it an error.
+//!
+//! ## RFC7807 and RFC9457
+//!
+//! RFC7807 has been obsoleted by RFC9457. By default this crate follows
+//! RFC7807. The rules of RFC9457 can be enabled with [set_rfc_mode]:
+//!
+//! ```rust
+//! use http_api_problem::*;
+//!
+//! set_rfc_mode(RfcMode::Rfc9457);
+//!
+//! let p = HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND);
+//!
+//! assert_eq!(Some("about:blank"), p.type_url.as_deref());
+//! assert_eq!(Some("Not Found"), p.title.as_deref());
+//! ```
+//!
+//! See [RfcMode] for the differences.
+//!
+//! ## Default Types
+//!
+//! Problems created from a status code only get a `type` derived from
+//! the status code. Which URI is used can be configured with
+//! [set_default_type_url]:
+//!
+//! ```rust
+//! use http_api_problem::*;
+//!
+//! set_default_type_url(DefaultTypeUrl::BaseUrl("https://docs.example.com/problems".into()));
+//!
+//! let p = HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND);
+//!
+//! assert_eq!(Some("https://docs.example.com/problems/404"), p.type_url.as_deref());
+//! ```
+//!
 //! ## Examples
 //!
 //! ```rust
@@ -71,6 +110,52 @@
 //! used as a fallback. This can be easily avoided by only using those constructor
 //! functions which require a [StatusCode].
 //!
+//! ## Problem Types
+//!
+//! The problem types of an application can be registered in a
+//! [ProblemTypeRegistry] which creates problems of the registered types
+//! and checks problems against them.
+//!
+//! ## Validation Errors
+//!
+//! [ValidationProblem] collects [InvalidParam]s and renders them as
+//! an `invalid-params` (RFC7807) or `errors` (RFC9457) member.
+//!
+//! With the feature `validator` the errors of the
+//! [validator](https://docs.rs/validator) crate can be converted into
+//! an [HttpApiProblem] or an `ApiError` with the status `422 - Unprocessable Entity`.
+//! The feature `garde` does the same for the reports of the
+//! [garde](https://docs.rs/garde) crate.
+//!
+//! ## Receiving Problems
+//!
+//! [HttpApiProblem::from_response] creates an [HttpApiProblem] from an
+//! [http::Response] received from another service. If the response does
+//! not contain an `application/problem+json` body, the problem is
+//! created from the status code.
+//!
+//! With the feature `reqwest` the trait `ResponseExt` turns responses
+//! of [reqwest](https://docs.rs/reqwest) into problems.
+//!
+//! ## Content Negotiation
+//!
+//! [ProblemFormat::negotiate] selects the format of a problem from the
+//! `Accept` header of a request: `application/problem+json`,
+//! `application/problem+xml` (with the feature `xml`), `application/json`,
+//! `text/html` or `text/plain`. The integrations which have access to the
+//! request use it: the `ProblemLayer` of the feature `tower`, the error handlers
+//! for actix-web, the catchers and responder for rocket, the catcher for salvo,
+//! the middleware for tide and `negotiate_warp_rejections` for warp.
+//!
+//! With axum, problems and the rejections of the extractors are negotiated by
+//! `ProblemLayer`, which picks up the problems axum's responses carry in their
+//! extensions. `recover_warp_rejection` and the responses for poem are always
+//! `application/problem+json` since they do not have access to the request.
+//!
+//! `text/html` is rendered by an [HtmlTemplate] which can be replaced
+//! with [set_html_template]. The [DefaultHtmlTemplate] escapes all values,
+//! links the `type` and shows the extension members in a table.
+//!
 //! ## Features
 //!
 //! ### JsonSchema
@@ -78,6 +163,18 @@
 //! The feature `json-schema` enables a derived implementation for
 //! JsonSchema, via `schemars`.
 //!
+//! ### XML
+//!
+//! The feature `xml` adds `xml_bytes`, `xml_string` and `from_xml` to
+//! write and parse problems as `application/problem+xml` in the
+//! `urn:ietf:rfc:7807` namespace.
+//!
+//! ### CBOR and MessagePack
+//!
+//! The feature `cbor` adds `to_cbor` and `from_cbor` for
+//! `application/problem+cbor`. The feature `msgpack` adds `to_msgpack`
+//! and `from_msgpack`. Both encode the same members as the JSON format.
+//!
 //! ### Web Frameworks
 //!
 //! There are multiple features to integrate with web frameworks:
@@ -89,15 +186,49 @@
 //! * `salvo`
 //! * `tide`
 //! * `rocket (v0.5.0-rc1)`
+//! * `poem`
 //!
 //! These mainly convert the `HttpApiProblem` to response types of
 //! the frameworks and implement traits to integrate with the frameworks
 //! error handling.
 //!
+//! With `axum`, the extractors `ProblemJson`, `ProblemPath`, `ProblemQuery`
+//! and `ProblemForm` reject with problems instead of plain text.
+//!
+//! With `actix-web`, `actix_json_error_handler` and its siblings for paths,
+//! queries and forms can be set as the `error_handler` of the extractor configs.
+//!
+//! With `rocket`, the catchers returned by `rocket_catchers` respond with
+//! problems for all status codes.
+//!
+//! With `salvo`, handlers can return `HttpApiProblem`s and `ApiError`s and
+//! the `Catcher` returned by `salvo_catcher` writes all errors as problems.
+//!
+//! With `tide`, the middleware `TideProblemMiddleware` turns all error
+//! responses into problems.
+//!
+//! With `poem`, `HttpApiProblem` and `ApiError` implement `ResponseError`.
+//! The feature `poem-openapi` additionally implements `ApiResponse` so that
+//! endpoints returning problems document them in the generated specs.
+//!
+//! With `tonic`, gRPC `Status`es convert into problems following the
+//! gRPC-HTTP status mapping and problems and `ApiError`s convert into
+//! `Status`es carrying the problem JSON as details. Only application
+//! metadata (see `is_application_metadata`) is copied into the problems.
+//!
+//! With `warp`, `recover_warp_rejection` can be passed to `Filter::recover`
+//! to render all rejections as problems. `negotiate_warp_rejections` wraps
+//! a filter to do the same in the format negotiated with the request.
+//!
 //! Additionally, the feature `rocket-okapi` (which implies the features
 //! `rocket` and `json-schema`) implements `rocket_okapi`'s `OpenApiResponder`
 //! for the json schema generated by the `json-schema` feature.
 //!
+//! ### Tower
+//!
+//! The feature `tower` adds `ProblemLayer`, a tower `Layer` which rewrites
+//! error responses of services without a framework into problems.
+//!
 //! ### ApiError
 //!
 //! The feature `api-error` enables a structure which can be
@@ -114,7 +245,10 @@ use std::convert::TryInto;
 use std::error::Error;
 use std::fmt;
 
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 use serde_json::Value;
 use std::collections::HashMap;
 
@@ -130,7 +264,77 @@ use schemars::JsonSchema;
 use actix_web_crate as actix_web;
 
 #[cfg(feature = "axum")]
-use axum_core;
+use axum_crate as axum;
+
+#[cfg(feature = "actix-web")]
+mod actix_ext;
+#[cfg(feature = "actix-web")]
+pub use actix_ext::*;
+#[cfg(feature = "axum")]
+mod axum_ext;
+#[cfg(feature = "axum")]
+pub use axum_ext::*;
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "cbor")]
+pub use cbor::*;
+mod client;
+pub use client::*;
+mod default_type_url;
+pub use default_type_url::*;
+mod html;
+pub use html::*;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+#[cfg(feature = "msgpack")]
+pub use msgpack::*;
+mod negotiation;
+pub use negotiation::*;
+#[cfg(feature = "poem-openapi")]
+mod poem_openapi_ext;
+#[cfg(feature = "reqwest")]
+mod reqwest_ext;
+#[cfg(feature = "reqwest")]
+pub use reqwest_ext::*;
+mod registry;
+pub use registry::*;
+mod rfc_mode;
+pub use rfc_mode::*;
+#[cfg(feature = "rocket")]
+mod rocket_ext;
+#[cfg(feature = "rocket")]
+pub use rocket_ext::*;
+#[cfg(feature = "salvo")]
+mod salvo_ext;
+#[cfg(feature = "salvo")]
+pub use salvo_ext::*;
+mod scoped_setting;
+mod status_deserialization;
+pub use status_deserialization::*;
+#[cfg(feature = "tide")]
+mod tide_ext;
+#[cfg(feature = "tide")]
+pub use tide_ext::*;
+#[cfg(feature = "tonic")]
+mod tonic_ext;
+#[cfg(feature = "tonic")]
+pub use tonic_ext::*;
+#[cfg(feature = "tower")]
+mod tower_ext;
+#[cfg(feature = "tower")]
+pub use tower_ext::*;
+mod typed;
+pub use typed::*;
+mod validation;
+pub use validation::*;
+#[cfg(feature = "warp")]
+mod warp_ext;
+#[cfg(feature = "warp")]
+pub use warp_ext::*;
+#[cfg(feature = "xml")]
+mod xml;
+#[cfg(feature = "xml")]
+pub use xml::*;
 
 pub use http::status::{InvalidStatusCode, StatusCode};
 
@@ -139,8 +343,19 @@ pub use http::status::{InvalidStatusCode, StatusCode};
 /// "application/problem+json"
 pub static PROBLEM_JSON_MEDIA_TYPE: &str = "application/problem+json";
 
+/// The recommended media type when serialized to XML
+///
+/// "application/problem+xml"
+pub static PROBLEM_XML_MEDIA_TYPE: &str = "application/problem+xml";
+
+/// The media type when serialized to CBOR
+///
+/// "application/problem+cbor"
+pub static PROBLEM_CBOR_MEDIA_TYPE: &str = "application/problem+cbor";
+
 /// Description of a problem that can be returned by an HTTP API
 /// based on [RFC7807](https://tools.ietf.org/html/rfc7807)
+/// or [RFC9457](https://www.rfc-editor.org/rfc/rfc9457)
 ///
 /// # Example
 ///
@@ -172,11 +387,17 @@ pub static PROBLEM_JSON_MEDIA_TYPE: &str = "application/problem+json";
 /// default to `500`.
 ///
 /// When receiving an [HttpApiProblem] there might be an invalid
-/// [StatusCode] contained. In this case the `status` field will be empty.
+/// [StatusCode] contained. In this case the `status` field will be empty
+/// and the value received is available via [HttpApiProblem::raw_status].
 /// This is a trade off so that the recipient does not have to deal with
 /// another error and can still have access to the remaining fields of the
-/// struct.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// struct. See [StatusDeserialization] to make it an error instead.
+///
+/// # Serialization
+///
+/// How an [HttpApiProblem] is serialized and deserialized depends on
+/// the [RfcMode] in effect. See [rfc_mode].
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 #[cfg_attr(
     feature = "json-schema",
@@ -191,8 +412,7 @@ pub struct HttpApiProblem {
     /// problem type (e.g., using HTML [W3C.REC-html5-20141028]).  When
     /// this member is not present, its value is assumed to be
     /// "about:blank".
-    #[serde(rename = "type")]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "json-schema", serde(rename = "type"))]
     #[cfg_attr(
         feature = "json-schema",
         schemars(
@@ -203,10 +423,8 @@ pub struct HttpApiProblem {
 
     /// The HTTP status code [RFC7231, Section 6](https://tools.ietf.org/html/rfc7231#section-6)
     /// generated by the origin server for this occurrence of the problem.
-    #[serde(default)]
-    #[serde(with = "custom_http_status_serialization")]
+    #[cfg_attr(feature = "json-schema", serde(default))]
     #[cfg_attr(feature = "json-schema", schemars(with = "u16"))]
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<StatusCode>,
 
     /// A short, human-readable summary of the problem
@@ -220,26 +438,27 @@ pub struct HttpApiProblem {
             description = "A short, human-readable summary of the problem type. It SHOULD NOT change from occurrence to occurrence of the problem."
         )
     )]
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
 
     /// A human-readable explanation specific to this
     /// occurrence of the problem.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub detail: Option<String>,
 
     /// A URI reference that identifies the specific
     /// occurrence of the problem.  It may or may not yield further
     /// information if dereferenced.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub instance: Option<String>,
 
     /// Additional fields that must be JSON values
     ///
     /// These values get serialized into the JSON
     /// on top level.
-    #[serde(flatten)]
+    #[cfg_attr(feature = "json-schema", serde(flatten))]
     additional_fields: HashMap<String, serde_json::Value>,
+
+    /// The `status` member received if it was not a valid [StatusCode]
+    #[cfg_attr(feature = "json-schema", schemars(skip))]
+    raw_status: Option<serde_json::Value>,
 }
 
 impl HttpApiProblem {
@@ -340,6 +559,10 @@ impl HttpApiProblem {
     /// Creates a new instance with the `title` and `type_url` derived from the
     /// [StatusCode].
     ///
+    /// The `type_url` is derived from the status according to the
+    /// [DefaultTypeUrl] in effect. With [RfcMode::Rfc9457] it will be
+    /// `about:blank` unless configured otherwise.
+    ///
     /// #Example
     ///
     /// ```rust
@@ -355,7 +578,9 @@ impl HttpApiProblem {
     /// ```
     pub fn with_title_and_type<T: Into<StatusCode>>(status: T) -> Self {
         let status = status.into();
-        Self::with_title(status).type_url(format!("https://httpstatuses.com/{}", status.as_u16()))
+        let mut problem = Self::with_title(status);
+        problem.type_url = default_type_url().type_url(status);
+        problem
     }
 
     /// Creates a new instance with the `title` and `type_url` derived from the
@@ -402,6 +627,7 @@ impl HttpApiProblem {
             detail: None,
             instance: None,
             additional_fields: Default::default(),
+            raw_status: None,
         }
     }
 
@@ -422,9 +648,35 @@ impl HttpApiProblem {
     /// ```
     pub fn status<T: Into<StatusCode>>(mut self, status: T) -> Self {
         self.status = Some(status.into());
+        self.raw_status = None;
         self
     }
 
+    /// Returns the `status` member as received if it could not be
+    /// deserialized into a [StatusCode].
+    ///
+    /// This is only the case with [StatusDeserialization::Lenient]. The value
+    /// is serialized again as long as `status` is `None`.
+    ///
+    /// #Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// let p: HttpApiProblem = serde_json::from_str(r#"{"status": "teapot"}"#).unwrap();
+    ///
+    /// assert_eq!(None, p.status);
+    /// assert_eq!(Some(&serde_json::json!("teapot")), p.raw_status());
+    ///
+    /// let p: HttpApiProblem = serde_json::from_str(r#"{"status": "404"}"#).unwrap();
+    ///
+    /// assert_eq!(Some(StatusCode::NOT_FOUND), p.status);
+    /// assert_eq!(None, p.raw_status());
+    /// ```
+    pub fn raw_status(&self) -> Option<&serde_json::Value> {
+        self.raw_status.as_ref()
+    }
+
     /// Sets the `type_url`
     ///
     /// #Example
@@ -806,6 +1058,28 @@ impl HttpApiProblem {
         Response::from_parts(parts, body).into()
     }
 
+    /// Creates a [poem] `application/problem+json` response.
+    ///
+    /// If status is `None` `500 - Internal Server Error` is the
+    /// default.
+    ///
+    /// The format is not negotiated since poem's `ResponseError`
+    /// does not have access to the request.
+    ///
+    /// Requires the `poem` feature
+    #[cfg(feature = "poem")]
+    pub fn to_poem_response(&self) -> poem::Response {
+        use http::header::{HeaderValue, CONTENT_TYPE};
+
+        poem::Response::builder()
+            .status(self.status_or_internal_server_error())
+            .header(
+                CONTENT_TYPE,
+                HeaderValue::from_static(PROBLEM_JSON_MEDIA_TYPE),
+            )
+            .body(self.json_bytes())
+    }
+
     /// Creates a [tide] response.
     ///
     /// If status is `None` `500 - Internal Server Error` is the
@@ -824,6 +1098,37 @@ impl HttpApiProblem {
             .build()
     }
 
+    /// Creates a [warp] response.
+    ///
+    /// If status is `None` `500 - Internal Server Error` is the
+    /// default.
+    ///
+    /// Requires the `warp` feature
+    #[cfg(feature = "warp")]
+    pub fn to_warp_response(&self) -> warp::reply::Response {
+        use warp::http::header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
+        use warp::http::StatusCode;
+        use warp::reply::Response;
+
+        let json = self.json_string();
+        let length = json.len() as u64;
+
+        let (mut parts, body) = Response::new(json.into()).into_parts();
+
+        parts.headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(PROBLEM_JSON_MEDIA_TYPE),
+        );
+        parts.headers.insert(
+            CONTENT_LENGTH,
+            HeaderValue::from_str(&length.to_string()).unwrap(),
+        );
+        parts.status = StatusCode::from_u16(self.status_code_or_internal_server_error())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        Response::from_parts(parts, body)
+    }
+
     #[allow(dead_code)]
     fn status_or_internal_server_error(&self) -> StatusCode {
         self.status.unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
@@ -947,8 +1252,15 @@ impl From<HttpApiProblem> for axum_core::response::Response {
 
 #[cfg(feature = "axum")]
 impl axum_core::response::IntoResponse for HttpApiProblem {
+    /// Creates an `application/problem+json` response.
+    ///
+    /// The problem is also put into the extensions of the response so that
+    /// the `ProblemLayer` of the `tower` feature can render it in the format
+    /// negotiated with the client.
     fn into_response(self) -> axum_core::response::Response {
-        self.into()
+        let mut response = self.to_axum_response();
+        response.extensions_mut().insert(self);
+        response
     }
 }
 
@@ -990,8 +1302,9 @@ impl From<HttpApiProblem> for ::rocket::Response<'static> {
 
 #[cfg(feature = "rocket")]
 impl<'r> ::rocket::response::Responder<'r, 'static> for HttpApiProblem {
-    fn respond_to(self, _request: &::rocket::Request) -> ::rocket::response::Result<'static> {
-        Ok(self.into())
+    /// Responds in the format negotiated with the `Accept` header of the request.
+    fn respond_to(self, request: &::rocket::Request) -> ::rocket::response::Result<'static> {
+        Ok(rocket_ext::negotiated_response(&self, request))
     }
 }
 
@@ -1014,6 +1327,33 @@ impl rocket_okapi::response::OpenApiResponderInner for HttpApiProblem {
 #[cfg(feature = "warp")]
 impl warp::reject::Reject for HttpApiProblem {}
 
+/// Creates a [warp::reply::Response] from something that can become an
+/// `HttpApiProblem`.
+///
+/// If status is `None` `500 - Internal Server Error` is the
+/// default.
+///
+/// Requires the `warp` feature
+#[cfg(feature = "warp")]
+pub fn into_warp_response<T: Into<HttpApiProblem>>(what: T) -> warp::reply::Response {
+    let problem: HttpApiProblem = what.into();
+    problem.to_warp_response()
+}
+
+#[cfg(feature = "warp")]
+impl From<HttpApiProblem> for warp::reply::Response {
+    fn from(problem: HttpApiProblem) -> warp::reply::Response {
+        problem.to_warp_response()
+    }
+}
+
+#[cfg(feature = "warp")]
+impl warp::Reply for HttpApiProblem {
+    fn into_response(self) -> warp::reply::Response {
+        self.to_warp_response()
+    }
+}
+
 /// Creates a [salvo::Response] from something that can become an
 /// `HttpApiProblem`.
 ///
@@ -1032,6 +1372,44 @@ impl From<HttpApiProblem> for salvo::Response {
     }
 }
 
+/// Creates a [poem::Response] from something that can become an
+/// `HttpApiProblem`.
+///
+/// If status is `None` `500 - Internal Server Error` is the
+/// default.
+///
+/// Requires the `poem` feature
+#[cfg(feature = "poem")]
+pub fn into_poem_response<T: Into<HttpApiProblem>>(what: T) -> poem::Response {
+    let problem: HttpApiProblem = what.into();
+    problem.to_poem_response()
+}
+
+#[cfg(feature = "poem")]
+impl From<HttpApiProblem> for poem::Response {
+    fn from(problem: HttpApiProblem) -> poem::Response {
+        problem.to_poem_response()
+    }
+}
+
+#[cfg(feature = "poem")]
+impl poem::IntoResponse for HttpApiProblem {
+    fn into_response(self) -> poem::Response {
+        self.to_poem_response()
+    }
+}
+
+#[cfg(feature = "poem")]
+impl poem::error::ResponseError for HttpApiProblem {
+    fn status(&self) -> poem::http::StatusCode {
+        self.status_or_internal_server_error()
+    }
+
+    fn as_response(&self) -> poem::Response {
+        self.to_poem_response()
+    }
+}
+
 /// Creates a [tide::Response] from something that can become an
 /// `HttpApiProblem`.
 ///
@@ -1050,10 +1428,126 @@ impl From<HttpApiProblem> for tide::Response {
     }
 }
 
+impl Serialize for HttpApiProblem {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        let mode = rfc_mode();
+
+        let title = match mode {
+            RfcMode::Rfc9457 if self.type_url.as_deref().unwrap_or(ABOUT_BLANK) == ABOUT_BLANK => {
+                // With "about:blank" the title should be the reason phrase of the status
+                self.status
+                    .and_then(|s| s.canonical_reason())
+                    .or(self.title.as_deref())
+            }
+            _ => self.title.as_deref(),
+        };
+
+        let mut map = serializer.serialize_map(None)?;
+
+        if let Some(type_url) = self.type_url.as_ref() {
+            map.serialize_entry("type", type_url)?;
+        }
+        if self.status.is_some() {
+            map.serialize_entry(
+                "status",
+                &custom_http_status_serialization::SerializeStatus(&self.status),
+            )?;
+        } else if let Some(raw_status) = self.raw_status.as_ref() {
+            map.serialize_entry("status", raw_status)?;
+        }
+        if let Some(title) = title {
+            map.serialize_entry("title", title)?;
+        }
+        if let Some(detail) = self.detail.as_ref() {
+            map.serialize_entry("detail", detail)?;
+        }
+        if let Some(instance) = self.instance.as_ref() {
+            map.serialize_entry("instance", instance)?;
+        }
+        for (key, value) in self.additional_fields.iter() {
+            if !is_member_name(key) {
+                map.serialize_entry(key, value)?;
+            }
+        }
+
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for HttpApiProblem {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mode = rfc_mode();
+        let mut members = serde_json::Map::<String, Value>::deserialize(deserializer)?;
+
+        // RFC9457 requires members with a value of the wrong type to be ignored
+        let mut take = |name: &str,
+                        f: fn(Value) -> Result<Option<String>, serde_json::Error>|
+         -> Result<Option<String>, D::Error> {
+            match members.remove(name).map(f) {
+                None => Ok(None),
+                Some(Ok(value)) => Ok(value),
+                Some(Err(_)) if mode == RfcMode::Rfc9457 => Ok(None),
+                Some(Err(err)) => Err(D::Error::custom(format!("member '{}': {}", name, err))),
+            }
+        };
+
+        let type_url = take("type", Option::deserialize)?;
+        let title = take("title", Option::deserialize)?;
+        let detail = take("detail", Option::deserialize)?;
+        let instance = take("instance", Option::deserialize)?;
+
+        let (status, raw_status) = match members.remove("status") {
+            None => (None, None),
+            Some(raw) => match custom_http_status_serialization::deserialize(&raw) {
+                Ok(status) => (status, None),
+                Err(err) if status_deserialization() == StatusDeserialization::Strict => {
+                    return Err(D::Error::custom(format!("member 'status': {}", err)))
+                }
+                Err(_) => (None, Some(raw)),
+            },
+        };
+
+        let type_url = match mode {
+            RfcMode::Rfc7807 => type_url,
+            RfcMode::Rfc9457 => type_url.or_else(|| Some(ABOUT_BLANK.to_string())),
+        };
+
+        Ok(HttpApiProblem {
+            type_url,
+            status,
+            title,
+            detail,
+            instance,
+            additional_fields: members.into_iter().collect(),
+            raw_status,
+        })
+    }
+}
+
 mod custom_http_status_serialization {
     use http::StatusCode;
-    use serde::{Deserialize, Deserializer, Serializer};
-    use std::convert::TryFrom;
+    use serde::{Serialize, Serializer};
+    use serde_json::Value;
+
+    /// Serializes an optional [StatusCode] as an integer
+    pub struct SerializeStatus<'a>(pub &'a Option<StatusCode>);
+
+    impl Serialize for SerializeStatus<'_> {
+        fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serialize(self.0, s)
+        }
+    }
 
     pub fn serialize<S>(status: &Option<StatusCode>, s: S) -> Result<S::Ok, S::Error>
     where
@@ -1065,22 +1559,22 @@ mod custom_http_status_serialization {
         s.serialize_none()
     }
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<StatusCode>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let s: Option<u16> = Option::deserialize(deserializer)?;
-        if let Some(numeric_status_code) = s {
-            // If the status code numeral is invalid we simply return None.
-            // This is a trade off to guarantee that the client can still
-            // have access to the rest of the problem struct instead of
-            // having to deal with an error caused by trying to deserialize an invalid status
-            // code. Additionally the received response still contains a status code.
-            let status_code = StatusCode::try_from(numeric_status_code).ok();
-            return Ok(status_code);
-        }
-
-        Ok(None)
+    /// Deserializes an optional [StatusCode] from an integer or a string containing an integer
+    ///
+    /// Fails if the value is not a valid [StatusCode]. What happens then
+    /// is decided by the [StatusDeserialization](crate::StatusDeserialization) in effect.
+    pub fn deserialize(value: &Value) -> Result<Option<StatusCode>, String> {
+        let code = match value {
+            Value::Null => return Ok(None),
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => s.trim().parse::<u64>().ok(),
+            _ => None,
+        };
+
+        code.and_then(|code| u16::try_from(code).ok())
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .map(Some)
+            .ok_or_else(|| format!("invalid status code: {}", value))
     }
 }
 
@@ -0,0 +1,46 @@
+//! The MessagePack encoding of problems.
+//!
+//! The members are encoded as a MessagePack map with the same keys as the JSON format.
+//! The `status` is an integer.
+//!
+//! Decoding goes through the same data model as JSON: extension members become
+//! [serde_json::Value]s. Binary data and maps with keys which are not strings
+//! can not be decoded and floats which are not finite become `null`.
+//!
+//! Requires the `msgpack` feature
+use super::*;
+
+/// The error when decoding a problem from MessagePack
+///
+/// Requires the `msgpack` feature
+pub type ProblemMsgpackError = rmp_serde::decode::Error;
+
+impl HttpApiProblem {
+    /// Serialize to a MessagePack `Vec<u8>`
+    ///
+    /// Requires the `msgpack` feature
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        rmp_serde::to_vec(self).unwrap()
+    }
+
+    /// Parses a problem from its MessagePack encoding.
+    ///
+    /// Requires the `msgpack` feature
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// let p = HttpApiProblem::new(StatusCode::FORBIDDEN)
+    ///     .title("You do not have enough credit.")
+    ///     .value("balance", &30);
+    ///
+    /// let parsed = HttpApiProblem::from_msgpack(p.to_msgpack()).unwrap();
+    ///
+    /// assert_eq!(parsed, p);
+    /// ```
+    pub fn from_msgpack<T: AsRef<[u8]>>(msgpack: T) -> Result<Self, ProblemMsgpackError> {
+        rmp_serde::from_slice(msgpack.as_ref())
+    }
+}
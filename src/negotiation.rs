@@ -0,0 +1,226 @@
+//! Choosing the format of a problem from the `Accept` header of a request.
+//!
+//! Problems are written as `application/problem+json` unless the client
+//! prefers another format which can be written. The integrations for
+//! the web frameworks use this when they have access to the request.
+use http::header::{HeaderMap, ACCEPT};
+
+use super::*;
+
+/// A format a problem can be written in
+///
+/// The order of the variants is the order of preference
+/// if a client accepts several formats with the same quality.
+///
+/// Formats may be added in later versions or by enabling features like `xml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum ProblemFormat {
+    /// `application/problem+json`
+    #[default]
+    ProblemJson,
+    /// `application/problem+xml`
+    ///
+    /// Requires the `xml` feature
+    #[cfg(feature = "xml")]
+    ProblemXml,
+    /// `application/json` with the same content as [ProblemFormat::ProblemJson]
+    /// for clients which do not know about problems
+    Json,
+    /// `text/html` rendered with the [HtmlTemplate] in effect
+    Html,
+    /// `text/plain` containing the [Display](fmt::Display) of the problem
+    PlainText,
+}
+
+impl ProblemFormat {
+    /// All formats in the order of preference
+    pub const ALL: &'static [ProblemFormat] = &[
+        ProblemFormat::ProblemJson,
+        #[cfg(feature = "xml")]
+        ProblemFormat::ProblemXml,
+        ProblemFormat::Json,
+        ProblemFormat::Html,
+        ProblemFormat::PlainText,
+    ];
+
+    /// Returns the media type of the format
+    pub fn media_type(self) -> &'static str {
+        match self {
+            ProblemFormat::ProblemJson => PROBLEM_JSON_MEDIA_TYPE,
+            #[cfg(feature = "xml")]
+            ProblemFormat::ProblemXml => PROBLEM_XML_MEDIA_TYPE,
+            ProblemFormat::Json => "application/json",
+            ProblemFormat::Html => "text/html",
+            ProblemFormat::PlainText => "text/plain",
+        }
+    }
+
+    /// Returns the value for the `Content-Type` header
+    ///
+    /// Textual formats get a `charset` parameter.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ProblemFormat::Html => "text/html; charset=utf-8",
+            ProblemFormat::PlainText => "text/plain; charset=utf-8",
+            other => other.media_type(),
+        }
+    }
+
+    /// Selects the format for the value of an `Accept` header.
+    ///
+    /// The format with the highest quality (`q` parameter) wins. The quality of a format
+    /// is taken from the most specific media range matching it. If several formats
+    /// have the same quality the one preferred by [ProblemFormat::ALL] wins.
+    ///
+    /// If the value is empty or no format is acceptable
+    /// [ProblemFormat::ProblemJson] is selected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// assert_eq!(ProblemFormat::negotiate("*/*"), ProblemFormat::ProblemJson);
+    /// assert_eq!(ProblemFormat::negotiate("application/json"), ProblemFormat::Json);
+    /// assert_eq!(
+    ///     ProblemFormat::negotiate("text/html,application/xhtml+xml,*/*;q=0.8"),
+    ///     ProblemFormat::Html
+    /// );
+    /// assert_eq!(
+    ///     ProblemFormat::negotiate("text/*;q=0.5, text/plain, application/json;q=0.7"),
+    ///     ProblemFormat::PlainText
+    /// );
+    /// assert_eq!(ProblemFormat::negotiate("image/png"), ProblemFormat::ProblemJson);
+    /// ```
+    pub fn negotiate(accept: &str) -> ProblemFormat {
+        let ranges = accept
+            .split(',')
+            .filter_map(MediaRange::parse)
+            .collect::<Vec<_>>();
+
+        let mut selected = ProblemFormat::ProblemJson;
+        let mut selected_quality = 0.0;
+
+        for &format in Self::ALL {
+            let quality = ranges
+                .iter()
+                .filter_map(|range| range.specificity(format.media_type()).map(|s| (s, range.q)))
+                .fold(None, |best: Option<(u8, f32)>, (s, q)| match best {
+                    Some((best_s, _)) if best_s >= s => best,
+                    _ => Some((s, q)),
+                })
+                .map(|(_, q)| q)
+                .unwrap_or(0.0);
+
+            if quality > selected_quality {
+                selected = format;
+                selected_quality = quality;
+            }
+        }
+
+        selected
+    }
+
+    /// Selects the format for the `Accept` headers of a request.
+    ///
+    /// See [ProblemFormat::negotiate]
+    pub fn from_headers(headers: &HeaderMap) -> ProblemFormat {
+        let accept = headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        Self::negotiate(&accept)
+    }
+}
+
+impl fmt::Display for ProblemFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.media_type())
+    }
+}
+
+impl HttpApiProblem {
+    /// Serialize to a `String` in the given [ProblemFormat]
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// let p = HttpApiProblem::new(StatusCode::NOT_FOUND).title("No such user");
+    ///
+    /// assert_eq!(p.format_string(ProblemFormat::Json), p.json_string());
+    /// assert_eq!(p.format_string(ProblemFormat::PlainText), "404 Not Found - No such user");
+    /// ```
+    pub fn format_string(&self, format: ProblemFormat) -> String {
+        match format {
+            ProblemFormat::ProblemJson | ProblemFormat::Json => self.json_string(),
+            #[cfg(feature = "xml")]
+            ProblemFormat::ProblemXml => self.xml_string(),
+            ProblemFormat::Html => self.html_string(),
+            ProblemFormat::PlainText => self.to_string(),
+        }
+    }
+
+    /// Serialize to a `Vec<u8>` in the given [ProblemFormat]
+    pub fn format_bytes(&self, format: ProblemFormat) -> Vec<u8> {
+        match format {
+            ProblemFormat::ProblemJson | ProblemFormat::Json => self.json_bytes(),
+            #[cfg(feature = "xml")]
+            ProblemFormat::ProblemXml => self.xml_bytes(),
+            other => self.format_string(other).into_bytes(),
+        }
+    }
+}
+
+/// A media range of an `Accept` header with its quality
+struct MediaRange<'a> {
+    main: &'a str,
+    sub: &'a str,
+    q: f32,
+}
+
+impl<'a> MediaRange<'a> {
+    /// Ranges which are malformed or have an invalid quality are skipped
+    fn parse(range: &'a str) -> Option<Self> {
+        let mut parts = range.split(';');
+        let (main, sub) = parts.next()?.trim().split_once('/')?;
+        if main.is_empty() || sub.is_empty() {
+            return None;
+        }
+
+        let mut q = 1.0;
+        for param in parts {
+            if let Some((name, value)) = param.split_once('=') {
+                if name.trim().eq_ignore_ascii_case("q") {
+                    q = value.trim().parse::<f32>().ok()?;
+                    if !(0.0..=1.0).contains(&q) {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        Some(MediaRange {
+            main: main.trim(),
+            sub: sub.trim(),
+            q,
+        })
+    }
+
+    /// Returns how specific this range matches the media type or `None` if it does not match
+    fn specificity(&self, media_type: &str) -> Option<u8> {
+        let (main, sub) = media_type.split_once('/')?;
+
+        match (self.main, self.sub) {
+            ("*", "*") => Some(0),
+            (m, "*") if m.eq_ignore_ascii_case(main) => Some(1),
+            (m, s) if m.eq_ignore_ascii_case(main) && s.eq_ignore_ascii_case(sub) => Some(2),
+            _ => None,
+        }
+    }
+}
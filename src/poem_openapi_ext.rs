@@ -0,0 +1,96 @@
+//! Problems as responses of [poem_openapi] endpoints.
+//!
+//! Requires the `poem-openapi` feature
+use poem_openapi::registry::{
+    MetaMediaType, MetaResponse, MetaResponses, MetaSchema, MetaSchemaRef, Registry,
+};
+use poem_openapi::ApiResponse;
+
+use super::*;
+
+const SCHEMA_NAME: &str = "HttpApiProblem";
+
+impl ApiResponse for HttpApiProblem {
+    /// Request parsing errors of the endpoint also become problems.
+    const BAD_REQUEST_HANDLER: bool = true;
+
+    /// A `default` response with an `application/problem+json` body.
+    fn meta() -> MetaResponses {
+        problem_responses()
+    }
+
+    fn register(registry: &mut Registry) {
+        registry.create_schema::<HttpApiProblem, _>(SCHEMA_NAME.to_string(), |_| {
+            let string = || MetaSchemaRef::Inline(Box::new(MetaSchema::new("string")));
+            let uri = || {
+                MetaSchemaRef::Inline(Box::new(MetaSchema::new_with_format(
+                    "string",
+                    "uri-reference",
+                )))
+            };
+
+            MetaSchema {
+                rust_typename: Some(std::any::type_name::<HttpApiProblem>()),
+                description: Some("Problem Details for HTTP APIs"),
+                properties: vec![
+                    ("type", uri()),
+                    (
+                        "status",
+                        MetaSchemaRef::Inline(Box::new(MetaSchema::new_with_format(
+                            "integer", "int32",
+                        ))),
+                    ),
+                    ("title", string()),
+                    ("detail", string()),
+                    ("instance", uri()),
+                ],
+                additional_properties: Some(Box::new(MetaSchemaRef::Inline(Box::new(
+                    MetaSchema::ANY,
+                )))),
+                ..MetaSchema::new("object")
+            }
+        });
+    }
+
+    /// The problem gets the status of the error and its message as the `detail`.
+    fn from_parse_request_error(err: poem::Error) -> Self {
+        HttpApiProblem::with_title_and_type(err.status()).detail(err.to_string())
+    }
+}
+
+#[cfg(feature = "api-error")]
+impl ApiResponse for ApiError {
+    /// Request parsing errors of the endpoint also become errors.
+    const BAD_REQUEST_HANDLER: bool = true;
+
+    /// A `default` response with an `application/problem+json` body.
+    fn meta() -> MetaResponses {
+        problem_responses()
+    }
+
+    fn register(registry: &mut Registry) {
+        HttpApiProblem::register(registry)
+    }
+
+    /// The error gets the status of the error and its message.
+    fn from_parse_request_error(err: poem::Error) -> Self {
+        ApiError::builder(err.status())
+            .message(err.to_string())
+            .finish()
+    }
+}
+
+fn problem_responses() -> MetaResponses {
+    MetaResponses {
+        responses: vec![MetaResponse {
+            description: "A problem",
+            status: None,
+            status_range: None,
+            content: vec![MetaMediaType {
+                content_type: PROBLEM_JSON_MEDIA_TYPE,
+                schema: MetaSchemaRef::Reference(SCHEMA_NAME.to_string()),
+            }],
+            headers: vec![],
+        }],
+    }
+}
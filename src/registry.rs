@@ -0,0 +1,299 @@
+//! A registry for the problem types of an application
+//!
+//! Registering all problem types of an application in one place
+//! keeps their type URIs, titles and status codes stable and documented.
+use std::error::Error;
+use std::fmt;
+
+use super::*;
+
+/// Describes a problem type of an application
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let out_of_credit = ProblemType::new(
+///     "https://example.com/probs/out-of-credit",
+///     "You do not have enough credit.",
+///     StatusCode::FORBIDDEN,
+/// )
+/// .description("The account does not have enough credit for the transaction.")
+/// .extension_member("balance")
+/// .extension_member("accounts");
+///
+/// let p = out_of_credit.problem().value("balance", &30);
+///
+/// assert_eq!(Some(StatusCode::FORBIDDEN), p.status);
+/// assert_eq!(Some("You do not have enough credit."), p.title.as_deref());
+/// assert_eq!(Some("https://example.com/probs/out-of-credit"), p.type_url.as_deref());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemType {
+    /// The URI identifying the problem type
+    pub type_url: String,
+
+    /// A short, human-readable summary of the problem type
+    pub title: String,
+
+    /// The [StatusCode] used for problems of this type
+    pub status: StatusCode,
+
+    /// A human-readable description of the problem type
+    pub description: Option<String>,
+
+    /// The names of the extension members problems of this type may contain
+    pub extension_members: Vec<String>,
+}
+
+impl ProblemType {
+    /// Creates a new problem type
+    pub fn new<U, T, S>(type_url: U, title: T, status: S) -> Self
+    where
+        U: Into<String>,
+        T: Into<String>,
+        S: Into<StatusCode>,
+    {
+        ProblemType {
+            type_url: type_url.into(),
+            title: title.into(),
+            status: status.into(),
+            description: None,
+            extension_members: Vec::new(),
+        }
+    }
+
+    /// Sets the `description`
+    pub fn description<T: Into<String>>(mut self, description: T) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Adds the name of an extension member problems of this type may contain
+    pub fn extension_member<T: Into<String>>(mut self, name: T) -> Self {
+        self.extension_members.push(name.into());
+        self
+    }
+
+    /// Creates an [HttpApiProblem] of this type
+    pub fn problem(&self) -> HttpApiProblem {
+        HttpApiProblem::new(self.status)
+            .type_url(self.type_url.clone())
+            .title(self.title.clone())
+    }
+
+    /// Creates an [ApiErrorBuilder] for an [ApiError] of this type
+    ///
+    /// Requires the `api-error` feature
+    #[cfg(feature = "api-error")]
+    pub fn api_error(&self) -> ApiErrorBuilder {
+        ApiError::builder(self.status)
+            .type_url(&self.type_url)
+            .title(&self.title)
+    }
+}
+
+/// The registered problem types of an application
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let registry = ProblemTypeRegistry::new().with(ProblemType::new(
+///     "https://example.com/probs/out-of-credit",
+///     "You do not have enough credit.",
+///     StatusCode::FORBIDDEN,
+/// ));
+///
+/// let p = registry
+///     .problem("https://example.com/probs/out-of-credit")
+///     .unwrap()
+///     .detail("Your current balance is 30, but that costs 50.");
+///
+/// assert!(registry.check(&p).is_ok());
+///
+/// assert!(registry.problem("https://example.com/probs/unknown").is_err());
+///
+/// let p = HttpApiProblem::new(StatusCode::FORBIDDEN).type_url("https://example.com/probs/unknown");
+///
+/// assert!(registry.check(&p).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProblemTypeRegistry {
+    types: HashMap<String, ProblemType>,
+    default_type_url: Option<DefaultTypeUrl>,
+}
+
+impl ProblemTypeRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a [ProblemType]
+    ///
+    /// Returns the [ProblemType] previously registered with the same type URI.
+    pub fn register(&mut self, problem_type: ProblemType) -> Option<ProblemType> {
+        self.types
+            .insert(problem_type.type_url.clone(), problem_type)
+    }
+
+    /// Registers a [ProblemType]
+    ///
+    /// A [ProblemType] previously registered with the same type URI will be replaced.
+    pub fn with(mut self, problem_type: ProblemType) -> Self {
+        self.register(problem_type);
+        self
+    }
+
+    /// Sets the [DefaultTypeUrl] used for problems which are created from a status code only.
+    ///
+    /// If not set the [DefaultTypeUrl] in effect is used (see [default_type_url]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// let registry = ProblemTypeRegistry::new()
+    ///     .default_type_url(DefaultTypeUrl::BaseUrl("https://docs.example.com/problems".into()));
+    ///
+    /// let p = registry.status_problem(StatusCode::NOT_FOUND);
+    ///
+    /// assert_eq!(Some("https://docs.example.com/problems/404"), p.type_url.as_deref());
+    /// assert_eq!(Some("Not Found"), p.title.as_deref());
+    /// assert!(registry.check(&p).is_ok());
+    /// ```
+    pub fn default_type_url(mut self, default: DefaultTypeUrl) -> Self {
+        self.default_type_url = Some(default);
+        self
+    }
+
+    /// Creates an [HttpApiProblem] with the `title` and `type` derived from the [StatusCode].
+    ///
+    /// The `type` is derived using the [DefaultTypeUrl] of this registry.
+    pub fn status_problem<T: Into<StatusCode>>(&self, status: T) -> HttpApiProblem {
+        let status = status.into();
+        let mut problem = HttpApiProblem::with_title(status);
+        problem.type_url = self.effective_default_type_url().type_url(status);
+        problem
+    }
+
+    /// Returns the [ProblemType] registered for the type URI
+    pub fn get(&self, type_url: &str) -> Option<&ProblemType> {
+        self.types.get(type_url)
+    }
+
+    /// Returns `true` if a [ProblemType] is registered for the type URI
+    pub fn is_registered(&self, type_url: &str) -> bool {
+        self.types.contains_key(type_url)
+    }
+
+    /// Returns all registered [ProblemType]s
+    pub fn problem_types(&self) -> impl Iterator<Item = &ProblemType> {
+        self.types.values()
+    }
+
+    /// Creates an [HttpApiProblem] of the type registered for the type URI
+    ///
+    /// Fails if there is no such type registered.
+    pub fn problem(&self, type_url: &str) -> Result<HttpApiProblem, ProblemTypeError> {
+        self.get_or_err(type_url).map(ProblemType::problem)
+    }
+
+    /// Creates an [ApiErrorBuilder] for an [ApiError] of the type registered for the type URI
+    ///
+    /// Fails if there is no such type registered.
+    ///
+    /// Requires the `api-error` feature
+    #[cfg(feature = "api-error")]
+    pub fn api_error(&self, type_url: &str) -> Result<ApiErrorBuilder, ProblemTypeError> {
+        self.get_or_err(type_url).map(ProblemType::api_error)
+    }
+
+    /// Checks that the type of the [HttpApiProblem] is registered and that it
+    /// only contains the extension members of the registered type.
+    ///
+    /// Problems without a type, with the type `about:blank` or with the
+    /// type derived from their status code as done by
+    /// [ProblemTypeRegistry::status_problem] are always valid.
+    pub fn check(&self, problem: &HttpApiProblem) -> Result<(), ProblemTypeError> {
+        let type_url = match problem.type_url.as_deref() {
+            None => return Ok(()),
+            Some(type_url) if type_url == ABOUT_BLANK => return Ok(()),
+            Some(type_url) if self.is_default_type_url(type_url, problem.status) => return Ok(()),
+            Some(type_url) => type_url,
+        };
+
+        let problem_type = self.get_or_err(type_url)?;
+
+        if let Some(member) = problem
+            .additional_fields()
+            .keys()
+            .find(|k| !problem_type.extension_members.contains(k))
+        {
+            return Err(ProblemTypeError::UndeclaredMember {
+                type_url: type_url.to_string(),
+                member: member.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Like [ProblemTypeRegistry::check] but panics if the check fails in debug builds.
+    ///
+    /// Does nothing in release builds.
+    pub fn debug_check(&self, problem: &HttpApiProblem) {
+        if cfg!(debug_assertions) {
+            if let Err(err) = self.check(problem) {
+                panic!("{}", err);
+            }
+        }
+    }
+
+    fn effective_default_type_url(&self) -> DefaultTypeUrl {
+        self.default_type_url
+            .clone()
+            .unwrap_or_else(default_type_url)
+    }
+
+    fn is_default_type_url(&self, type_url: &str, status: Option<StatusCode>) -> bool {
+        status
+            .and_then(|status| self.effective_default_type_url().type_url(status))
+            .is_some_and(|default| default == type_url)
+    }
+
+    fn get_or_err(&self, type_url: &str) -> Result<&ProblemType, ProblemTypeError> {
+        self.get(type_url)
+            .ok_or_else(|| ProblemTypeError::Unregistered(type_url.to_string()))
+    }
+}
+
+/// An error when a problem does not match the registered [ProblemType]s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemTypeError {
+    /// There is no [ProblemType] with the type URI
+    Unregistered(String),
+    /// The problem contains an extension member the [ProblemType] does not declare
+    UndeclaredMember { type_url: String, member: String },
+}
+
+impl fmt::Display for ProblemTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemTypeError::Unregistered(type_url) => {
+                write!(f, "problem type '{}' is not registered", type_url)
+            }
+            ProblemTypeError::UndeclaredMember { type_url, member } => write!(
+                f,
+                "problem type '{}' does not declare the extension member '{}'",
+                type_url, member
+            ),
+        }
+    }
+}
+
+impl Error for ProblemTypeError {}
@@ -0,0 +1,53 @@
+//! Turning responses of [reqwest] into problems
+use std::future::Future;
+
+use super::*;
+
+/// Extension methods for a [reqwest::Response]
+///
+/// Requires the `reqwest` feature
+pub trait ResponseExt: Sized {
+    /// Returns the response if its status is a success and an [HttpApiProblem] otherwise.
+    ///
+    /// See [HttpApiProblem::from_response] on how the problem is created.
+    fn error_for_problem(self) -> impl Future<Output = Result<Self, HttpApiProblem>> + Send;
+
+    /// Returns the response if its status is a success and an [ApiError] otherwise.
+    ///
+    /// The [ApiError] is created from the [HttpApiProblem] returned by
+    /// [ResponseExt::error_for_problem]. The [StatusCode] and the
+    /// [HeaderMap](http::HeaderMap) of the response will be
+    /// put into the [extensions](ApiError::extensions) of the [ApiError].
+    ///
+    /// Requires the `api-error` feature
+    #[cfg(feature = "api-error")]
+    fn error_for_api_error(self) -> impl Future<Output = Result<Self, ApiError>> + Send;
+}
+
+impl ResponseExt for reqwest::Response {
+    async fn error_for_problem(self) -> Result<Self, HttpApiProblem> {
+        let status = self.status();
+
+        if status.is_success() {
+            return Ok(self);
+        }
+
+        let headers = self.headers().clone();
+        let body = self.bytes().await.unwrap_or_default();
+
+        Err(HttpApiProblem::from_response_parts(status, &headers, &body))
+    }
+
+    #[cfg(feature = "api-error")]
+    async fn error_for_api_error(self) -> Result<Self, ApiError> {
+        let status = self.status();
+        let headers = self.headers().clone();
+
+        self.error_for_problem().await.map_err(|problem| {
+            let mut error = problem.into_api_error();
+            error.extensions_mut().insert(status);
+            error.extensions_mut().insert(headers);
+            error
+        })
+    }
+}
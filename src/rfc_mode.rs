@@ -0,0 +1,148 @@
+//! Selection of the specification [HttpApiProblem](crate::HttpApiProblem)s conform to.
+//!
+//! [RFC7807](https://tools.ietf.org/html/rfc7807) has been obsoleted by
+//! [RFC9457](https://www.rfc-editor.org/rfc/rfc9457). Since RFC9457 changes
+//! some rules which affect the wire format, the specification to follow
+//! can be selected process wide via [set_rfc_mode] or temporarily for the
+//! current thread via [with_rfc_mode].
+//!
+//! [RfcMode::Rfc7807] is the default to keep the behaviour of earlier versions.
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The value of the `type` member which is assumed when it is absent.
+///
+/// "about:blank"
+pub static ABOUT_BLANK: &str = "about:blank";
+
+static RFC_MODE: AtomicU8 = AtomicU8::new(RfcMode::Rfc7807 as u8);
+
+thread_local! {
+    static RFC_MODE_OVERRIDE: Cell<Option<RfcMode>> = const { Cell::new(None) };
+}
+
+/// The specification to follow when creating, serializing
+/// and deserializing an [HttpApiProblem](crate::HttpApiProblem).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RfcMode {
+    /// Follow [RFC7807](https://tools.ietf.org/html/rfc7807)
+    ///
+    /// * [HttpApiProblem::with_title_and_type](crate::HttpApiProblem::with_title_and_type)
+    ///   sets a `type` pointing to a description of the status code by default
+    /// * Members with an invalid type make deserialization fail, except for
+    ///   the `status` (see [StatusDeserialization](crate::StatusDeserialization))
+    /// * Extension members may have any name which is not a member of
+    ///   the problem itself
+    #[default]
+    Rfc7807,
+    /// Follow [RFC9457](https://www.rfc-editor.org/rfc/rfc9457)
+    ///
+    /// * An absent `type` is treated as `about:blank`
+    /// * If the `type` is `about:blank` the `title` will be the reason
+    ///   phrase of the `status` when serialized
+    /// * [HttpApiProblem::with_title_and_type](crate::HttpApiProblem::with_title_and_type)
+    ///   sets the `type` to `about:blank` by default
+    /// * Members with an invalid type are ignored when deserializing, except for
+    ///   the `status` (see [StatusDeserialization](crate::StatusDeserialization))
+    /// * Extension member names should start with a letter, consist of letters,
+    ///   digits and `_` and be at least 3 characters long. This is not enforced
+    ///   but can be checked with [RfcMode::is_valid_extension_name]
+    Rfc9457,
+}
+
+impl RfcMode {
+    /// Returns `true` if `name` may be used for an extension member in this mode.
+    ///
+    /// The names of the members defined by the specification are never
+    /// valid extension member names.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// assert!(RfcMode::Rfc7807.is_valid_extension_name("invalid-params"));
+    /// assert!(!RfcMode::Rfc9457.is_valid_extension_name("invalid-params"));
+    /// assert!(RfcMode::Rfc9457.is_valid_extension_name("invalid_params"));
+    /// assert!(!RfcMode::Rfc9457.is_valid_extension_name("id"));
+    /// assert!(!RfcMode::Rfc7807.is_valid_extension_name("title"));
+    /// ```
+    pub fn is_valid_extension_name(self, name: &str) -> bool {
+        if is_member_name(name) {
+            return false;
+        }
+
+        match self {
+            RfcMode::Rfc7807 => true,
+            RfcMode::Rfc9457 => {
+                let mut chars = name.chars();
+                chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+                    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    && name.len() >= 3
+            }
+        }
+    }
+
+    fn from_u8(v: u8) -> Self {
+        match v {
+            1 => RfcMode::Rfc9457,
+            _ => RfcMode::Rfc7807,
+        }
+    }
+}
+
+/// Returns the [RfcMode] in effect for the current thread.
+///
+/// This is the mode set with [with_rfc_mode] if called from within its closure
+/// and the process wide mode set with [set_rfc_mode] otherwise.
+pub fn rfc_mode() -> RfcMode {
+    RFC_MODE_OVERRIDE
+        .with(Cell::get)
+        .unwrap_or_else(|| RfcMode::from_u8(RFC_MODE.load(Ordering::Relaxed)))
+}
+
+/// Sets the process wide [RfcMode].
+///
+/// This should be done once when the application starts.
+pub fn set_rfc_mode(mode: RfcMode) {
+    RFC_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// Runs `f` with `mode` in effect for the current thread.
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let p = with_rfc_mode(RfcMode::Rfc9457, || {
+///     HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+/// });
+///
+/// assert_eq!(Some("about:blank"), p.type_url.as_deref());
+/// ```
+pub fn with_rfc_mode<F, T>(mode: RfcMode, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    struct Reset(Option<RfcMode>);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            RFC_MODE_OVERRIDE.with(|m| m.set(self.0));
+        }
+    }
+
+    let _reset = Reset(RFC_MODE_OVERRIDE.with(|m| m.replace(Some(mode))));
+
+    f()
+}
+
+/// Returns `true` if `name` is a member of the problem itself
+/// and therefore can not be used for an extension member.
+pub(crate) fn is_member_name(name: &str) -> bool {
+    matches!(
+        name,
+        "type" | "status" | "title" | "detail" | "instance" | "additional_fields"
+    )
+}
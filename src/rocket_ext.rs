@@ -0,0 +1,73 @@
+//! Catchers for [rocket] which respond with problems.
+//!
+//! Requires the `rocket` feature
+use std::io::Cursor;
+
+use rocket::http::{ContentType, Status};
+use rocket::{catch, catchers, Catcher, Request, Response};
+
+use super::*;
+
+/// A default catcher for [rocket] which responds with an `application/problem+json`
+/// for any status.
+///
+/// The `title` and `type` are derived from the status and the
+/// URI of the request becomes the `instance`. The format is negotiated
+/// with the `Accept` header of the request.
+///
+/// Requires the `rocket` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let rocket = rocket::build().register("/", rocket::catchers![rocket_default_catcher]);
+/// ```
+#[catch(default)]
+pub fn rocket_default_catcher(status: Status, request: &Request<'_>) -> HttpApiProblem {
+    let status = StatusCode::from_u16(status.code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+    HttpApiProblem::with_title_and_type(status).instance(request.uri().to_string())
+}
+
+/// Returns the catchers which respond with an `application/problem+json` for any status.
+///
+/// See [rocket_default_catcher]
+///
+/// Requires the `rocket` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let rocket = rocket::build().register("/", rocket_catchers());
+/// ```
+pub fn rocket_catchers() -> Vec<Catcher> {
+    catchers![rocket_default_catcher]
+}
+
+/// Creates a response in the format negotiated with the `Accept` header of the request
+pub(crate) fn negotiated_response(
+    problem: &HttpApiProblem,
+    request: &Request<'_>,
+) -> Response<'static> {
+    let accept = request
+        .headers()
+        .get("Accept")
+        .collect::<Vec<_>>()
+        .join(",");
+    let format = ProblemFormat::negotiate(&accept);
+
+    let content_type: ContentType = format.content_type().parse().unwrap();
+    let body = problem.format_bytes(format);
+
+    Response::build()
+        .status(Status {
+            code: problem.status_code_or_internal_server_error(),
+        })
+        .sized_body(body.len(), Cursor::new(body))
+        .header(content_type)
+        .finalize()
+}
@@ -0,0 +1,102 @@
+//! Writing problems with [salvo].
+//!
+//! Requires the `salvo` feature
+use salvo::catcher::Catcher;
+use salvo::http::header::{HeaderValue, CONTENT_TYPE};
+use salvo::http::{ResBody, StatusError};
+use salvo::{async_trait, Depot, FlowCtrl, Handler, Request, Response, Scribe};
+
+use super::*;
+
+impl Scribe for HttpApiProblem {
+    /// Writes this as an `application/problem+json`.
+    ///
+    /// If status is `None` `500 - Internal Server Error` is the
+    /// default.
+    fn render(self, res: &mut Response) {
+        render_as(&self, ProblemFormat::ProblemJson, res)
+    }
+}
+
+#[cfg(feature = "api-error")]
+impl Scribe for ApiError {
+    /// Writes this as an `application/problem+json`.
+    fn render(self, res: &mut Response) {
+        self.into_http_api_problem().render(res)
+    }
+}
+
+impl From<StatusError> for HttpApiProblem {
+    /// Creates a problem with the status and name of the [StatusError].
+    ///
+    /// The `detail` is the detail of the [StatusError] or its brief if there is no detail.
+    fn from(error: StatusError) -> Self {
+        HttpApiProblem::with_title_and_type(error.code)
+            .title(error.name)
+            .detail(error.detail.unwrap_or(error.brief))
+    }
+}
+
+/// A hoop for a salvo [Catcher] which writes errors as `application/problem+json`.
+///
+/// A [StatusError] written to the response is turned into a problem. Otherwise
+/// the problem is created from the status of the response.
+/// The path and query of the request become the `instance` and the format
+/// is negotiated with the `Accept` header of the request.
+///
+/// Requires the `salvo` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+/// use salvo::catcher::Catcher;
+/// use salvo::{Router, Service};
+///
+/// let service = Service::new(Router::new()).catcher(Catcher::default().hoop(SalvoProblemCatcher));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SalvoProblemCatcher;
+
+#[async_trait]
+impl Handler for SalvoProblemCatcher {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        _depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        let status = res.status_code.unwrap_or(StatusCode::NOT_FOUND);
+        if !status.is_client_error() && !status.is_server_error() {
+            return;
+        }
+
+        let mut problem = match res.take_body() {
+            ResBody::Error(error) => HttpApiProblem::from(error),
+            _ => HttpApiProblem::with_title_and_type(status),
+        };
+        problem.instance = req.uri().path_and_query().map(ToString::to_string);
+
+        render_as(&problem, ProblemFormat::from_headers(req.headers()), res);
+        ctrl.skip_rest();
+    }
+}
+
+fn render_as(problem: &HttpApiProblem, format: ProblemFormat, res: &mut Response) {
+    res.status_code(problem.status_or_internal_server_error());
+    res.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    res.body(problem.format_bytes(format));
+}
+
+/// Returns a [Catcher] which writes all errors as `application/problem+json`.
+///
+/// See [SalvoProblemCatcher]
+///
+/// Requires the `salvo` feature
+pub fn salvo_catcher() -> Catcher {
+    Catcher::default().hoop(SalvoProblemCatcher)
+}
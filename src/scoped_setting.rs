@@ -0,0 +1,72 @@
+//! The storage of settings which are configured process wide and can be
+//! overridden for the current thread, like the [DefaultTypeUrl](crate::DefaultTypeUrl).
+//!
+//! Settings which are `Copy` and fit into an atomic, like the [RfcMode](crate::RfcMode),
+//! do not use this since they are read whenever a problem is (de)serialized.
+use std::cell::RefCell;
+use std::sync::RwLock;
+use std::thread::LocalKey;
+
+/// A setting with a process wide value which can be overridden for the current thread
+///
+/// The thread local override has to be declared with `thread_local!` since
+/// it can not be part of a generic type.
+pub(crate) struct ScopedSetting<T: 'static> {
+    global: RwLock<Option<T>>,
+    local: &'static LocalKey<RefCell<Option<T>>>,
+}
+
+impl<T: Clone> ScopedSetting<T> {
+    pub(crate) const fn new(local: &'static LocalKey<RefCell<Option<T>>>) -> Self {
+        ScopedSetting {
+            global: RwLock::new(None),
+            local,
+        }
+    }
+
+    /// Returns the value overridden for the current thread or
+    /// the process wide value if there is no override
+    pub(crate) fn get(&self) -> Option<T> {
+        self.local.with(|v| v.borrow().clone()).or_else(|| {
+            self.global
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .clone()
+        })
+    }
+
+    /// Sets the process wide value
+    pub(crate) fn set(&self, value: Option<T>) {
+        *self
+            .global
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = value;
+    }
+
+    /// Runs `f` with `value` overriding the setting for the current thread.
+    ///
+    /// The previous override is restored afterwards, even if `f` panics.
+    pub(crate) fn scoped<F, R>(&self, value: T, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        struct Reset<T: 'static> {
+            local: &'static LocalKey<RefCell<Option<T>>>,
+            previous: Option<T>,
+        }
+
+        impl<T: 'static> Drop for Reset<T> {
+            fn drop(&mut self) {
+                let previous = self.previous.take();
+                self.local.with(|v| *v.borrow_mut() = previous);
+            }
+        }
+
+        let _reset = Reset {
+            local: self.local,
+            previous: self.local.with(|v| v.replace(Some(value))),
+        };
+
+        f()
+    }
+}
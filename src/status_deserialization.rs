@@ -0,0 +1,94 @@
+//! Selection of how an invalid `status` member is handled when deserializing
+//! an [HttpApiProblem](crate::HttpApiProblem).
+//!
+//! A `status` is valid if it is an integer or a string containing an integer
+//! which is a valid [StatusCode](crate::StatusCode). Other values are kept
+//! unless [StatusDeserialization::Strict] is chosen with [set_status_deserialization]
+//! or [with_status_deserialization], since earlier versions accepted any problem
+//! with an odd `status`.
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+static STATUS_DESERIALIZATION: AtomicU8 = AtomicU8::new(StatusDeserialization::Lenient as u8);
+
+thread_local! {
+    static STATUS_DESERIALIZATION_OVERRIDE: Cell<Option<StatusDeserialization>> = const { Cell::new(None) };
+}
+
+/// How an invalid `status` member is handled when deserializing
+/// an [HttpApiProblem](crate::HttpApiProblem).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StatusDeserialization {
+    /// An invalid `status` is kept
+    ///
+    /// * The `status` will be `None`
+    /// * The value received is available via
+    ///   [HttpApiProblem::raw_status](crate::HttpApiProblem::raw_status)
+    /// * The value received is serialized again as long as no `status` is set
+    #[default]
+    Lenient,
+    /// An invalid `status` makes deserialization fail
+    ///
+    /// This applies regardless of the [RfcMode](crate::RfcMode) in effect.
+    Strict,
+}
+
+impl StatusDeserialization {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            1 => StatusDeserialization::Strict,
+            _ => StatusDeserialization::Lenient,
+        }
+    }
+}
+
+/// Returns the [StatusDeserialization] in effect for the current thread.
+///
+/// This is the one set with [with_status_deserialization] if called from within its closure
+/// and the process wide one set with [set_status_deserialization] otherwise.
+pub fn status_deserialization() -> StatusDeserialization {
+    STATUS_DESERIALIZATION_OVERRIDE
+        .with(Cell::get)
+        .unwrap_or_else(|| {
+            StatusDeserialization::from_u8(STATUS_DESERIALIZATION.load(Ordering::Relaxed))
+        })
+}
+
+/// Sets the process wide [StatusDeserialization].
+///
+/// This should be done once when the application starts.
+pub fn set_status_deserialization(mode: StatusDeserialization) {
+    STATUS_DESERIALIZATION.store(mode as u8, Ordering::Relaxed);
+}
+
+/// Runs `f` with `mode` in effect for the current thread.
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let json = r#"{"title": "Odd", "status": 1000}"#;
+///
+/// let result = with_status_deserialization(StatusDeserialization::Strict, || {
+///     serde_json::from_str::<HttpApiProblem>(json)
+/// });
+///
+/// assert!(result.is_err());
+/// ```
+pub fn with_status_deserialization<F, T>(mode: StatusDeserialization, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    struct Reset(Option<StatusDeserialization>);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            STATUS_DESERIALIZATION_OVERRIDE.with(|m| m.set(self.0));
+        }
+    }
+
+    let _reset = Reset(STATUS_DESERIALIZATION_OVERRIDE.with(|m| m.replace(Some(mode))));
+
+    f()
+}
@@ -54,4 +54,2527 @@ mod serialization {
 
         assert_eq!(prob.status, None);
     }
+
+    #[test]
+    fn deserialize_status_from_string() {
+        let json = r#"{"title": "foo", "status": "404"}"#;
+
+        let prob: HttpApiProblem = serde_json::from_str(json).unwrap();
+
+        assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+        assert_eq!(prob.raw_status(), None);
+    }
+
+    #[test]
+    fn deserialize_invalid_status_keeps_the_raw_value() {
+        for status in [
+            json!(42),
+            json!(1000),
+            json!("teapot"),
+            json!(4.04),
+            json!([404]),
+        ] {
+            let json = json!({"title": "foo", "status": status});
+
+            let prob: HttpApiProblem = serde_json::from_value(json.clone()).unwrap();
+
+            assert_eq!(prob.status, None);
+            assert_eq!(prob.raw_status(), Some(&status));
+            assert_eq!(serde_json::to_value(&prob).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn setting_the_status_replaces_the_raw_value() {
+        let json = r#"{"title": "foo", "status": 1000}"#;
+
+        let prob: HttpApiProblem = serde_json::from_str(json).unwrap();
+        let prob = prob.status(StatusCode::BAD_GATEWAY);
+
+        assert_eq!(prob.raw_status(), None);
+        assert_eq!(serde_json::to_value(&prob).unwrap()["status"], json!(502));
+    }
+
+    #[test]
+    fn deserialize_invalid_status_strictly() {
+        use crate::{with_rfc_mode, with_status_deserialization, RfcMode, StatusDeserialization};
+
+        with_status_deserialization(StatusDeserialization::Strict, || {
+            for mode in [RfcMode::Rfc7807, RfcMode::Rfc9457] {
+                with_rfc_mode(mode, || {
+                    for status in [json!(42), json!(1000), json!("teapot"), json!({})] {
+                        let json = json!({"title": "foo", "status": status});
+                        let err = serde_json::from_value::<HttpApiProblem>(json).unwrap_err();
+                        assert!(err.to_string().contains("member 'status'"));
+                    }
+
+                    let json = json!({"title": "foo", "status": "503"});
+                    let prob: HttpApiProblem = serde_json::from_value(json).unwrap();
+                    assert_eq!(prob.status, Some(StatusCode::SERVICE_UNAVAILABLE));
+                });
+            }
+        });
+    }
+}
+
+mod rfc9457 {
+    use crate::*;
+    use serde_json::{self, json};
+
+    #[test]
+    fn with_title_and_type_uses_about_blank() {
+        let prob = with_rfc_mode(RfcMode::Rfc9457, || {
+            HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+        });
+
+        assert_eq!(prob.type_url.as_deref(), Some(ABOUT_BLANK));
+        assert_eq!(prob.title.as_deref(), Some("Not Found"));
+    }
+
+    #[test]
+    fn title_is_reason_phrase_for_about_blank() {
+        let prob = HttpApiProblem::new(StatusCode::NOT_FOUND).title("Nothing here");
+
+        let sample = with_rfc_mode(RfcMode::Rfc9457, || serde_json::to_value(&prob).unwrap());
+        let expected = json!({
+            "title": "Not Found",
+            "status": 404
+        });
+        assert_eq!(sample, expected);
+
+        let sample = serde_json::to_value(&prob).unwrap();
+        let expected = json!({
+            "title": "Nothing here",
+            "status": 404
+        });
+        assert_eq!(sample, expected);
+    }
+
+    #[test]
+    fn title_is_kept_for_other_types() {
+        let prob = HttpApiProblem::new(StatusCode::NOT_FOUND)
+            .title("Nothing here")
+            .type_url("https://example.com/probs/nothing");
+
+        let sample = with_rfc_mode(RfcMode::Rfc9457, || serde_json::to_value(&prob).unwrap());
+        let expected = json!({
+            "type": "https://example.com/probs/nothing",
+            "title": "Nothing here",
+            "status": 404
+        });
+        assert_eq!(sample, expected);
+    }
+
+    #[test]
+    fn deserialize_absent_type_as_about_blank() {
+        let json = r#"{"title": "foo", "status": 500}"#;
+
+        let prob: HttpApiProblem =
+            with_rfc_mode(RfcMode::Rfc9457, || serde_json::from_str(json).unwrap());
+        assert_eq!(prob.type_url.as_deref(), Some(ABOUT_BLANK));
+
+        let prob: HttpApiProblem = serde_json::from_str(json).unwrap();
+        assert_eq!(prob.type_url, None);
+    }
+
+    #[test]
+    fn deserialize_ignores_members_of_invalid_type() {
+        let json = r#"{"title": 42, "status": "teapot", "detail": "foo", "balance": 30}"#;
+
+        let prob: HttpApiProblem =
+            with_rfc_mode(RfcMode::Rfc9457, || serde_json::from_str(json).unwrap());
+        assert_eq!(prob.title, None);
+        assert_eq!(prob.status, None);
+        assert_eq!(prob.detail.as_deref(), Some("foo"));
+        assert_eq!(prob.get_value::<&str, u32>("balance"), Some(30));
+
+        assert!(serde_json::from_str::<HttpApiProblem>(json).is_err());
+    }
+
+    #[test]
+    fn extension_members_are_kept_regardless_of_their_names() {
+        with_rfc_mode(RfcMode::Rfc9457, || {
+            let mut prob = HttpApiProblem::new(StatusCode::BAD_REQUEST);
+            assert!(prob.try_set_value("invalid-params", &1).is_ok());
+            assert!(prob.try_set_value("id", &2).is_ok());
+            assert!(prob.try_set_value("title", &1).is_err());
+
+            let json = serde_json::to_value(&prob).unwrap();
+            assert_eq!(json["invalid-params"], json!(1));
+            assert_eq!(json["id"], json!(2));
+
+            let received: HttpApiProblem = serde_json::from_value(json).unwrap();
+            let forwarded = serde_json::to_value(&received).unwrap();
+            assert_eq!(forwarded["invalid-params"], json!(1));
+            assert_eq!(forwarded["id"], json!(2));
+        });
+    }
+
+    #[test]
+    fn reserved_names_are_not_serialized_as_extension_members() {
+        let mut prob = HttpApiProblem::new(StatusCode::BAD_REQUEST).title("foo");
+        prob.additional_fields_mut()
+            .insert("title".to_string(), json!("bar"));
+
+        let json = serde_json::to_value(&prob).unwrap();
+        assert_eq!(json["title"], json!("foo"));
+    }
+}
+
+#[cfg(feature = "xml")]
+mod xml {
+    use crate::*;
+
+    #[test]
+    fn roundtrip() {
+        let problem = with_rfc_mode(RfcMode::Rfc7807, || {
+            HttpApiProblem::new(StatusCode::FORBIDDEN)
+                .type_url("https://example.com/probs/out-of-credit")
+                .title("You do not have enough credit.")
+                .detail("Your current balance is 30, but that costs 50 & more.")
+                .instance("/account/12345/msgs/abc")
+                .value("balance", &30)
+                .value("blocked", &true)
+                .value("accounts", &["/account/12345", "/account/67890"])
+                .value(
+                    "limits",
+                    &serde_json::json!({"daily": 50, "nested": {"list": [1, [2, 3]]}}),
+                )
+        });
+
+        for xml in [problem.xml_bytes(), problem.xml_string().into_bytes()] {
+            let parsed = HttpApiProblem::from_xml(&xml).unwrap();
+            assert_eq!(parsed, problem);
+        }
+    }
+
+    #[test]
+    fn escapes_text() {
+        let problem = HttpApiProblem::new(StatusCode::BAD_REQUEST).detail("<b>a & b</b>");
+
+        let xml = String::from_utf8(problem.xml_bytes()).unwrap();
+
+        assert!(xml.contains("<detail>&lt;b&gt;a &amp; b&lt;/b&gt;</detail>"));
+        assert_eq!(
+            HttpApiProblem::from_xml(xml).unwrap().detail.as_deref(),
+            Some("<b>a & b</b>")
+        );
+    }
+
+    #[test]
+    fn leaves_out_members_with_invalid_names() {
+        let problem = with_rfc_mode(RfcMode::Rfc7807, || {
+            HttpApiProblem::new(StatusCode::BAD_REQUEST)
+                .value("not valid", &1)
+                .value("x-request-id", &"abc")
+        });
+
+        let xml = String::from_utf8(problem.xml_bytes()).unwrap();
+
+        assert!(!xml.contains("not valid"));
+        assert!(xml.contains("<x-request-id>abc</x-request-id>"));
+    }
+
+    #[test]
+    fn empty_elements() {
+        let problem = HttpApiProblem::from_xml(
+            r#"<p:problem xmlns:p="urn:ietf:rfc:7807"><p:status>404</p:status><p:title/><p:gone/></p:problem>"#,
+        )
+        .unwrap();
+
+        assert_eq!(problem.status, Some(StatusCode::NOT_FOUND));
+        assert_eq!(problem.title, None);
+        assert_eq!(problem.json_value("gone"), Some(&serde_json::Value::Null));
+
+        let problem = HttpApiProblem::from_xml(r#"<problem xmlns="urn:ietf:rfc:7807"/>"#).unwrap();
+        assert_eq!(problem.status, None);
+    }
+
+    #[test]
+    fn requires_the_namespace() {
+        let err = HttpApiProblem::from_xml("<problem><status>404</status></problem>").unwrap_err();
+        assert!(matches!(err, ProblemXmlError::NotAProblem));
+
+        let err = HttpApiProblem::from_xml(r#"<error xmlns="urn:ietf:rfc:7807"/>"#).unwrap_err();
+        assert!(matches!(err, ProblemXmlError::NotAProblem));
+    }
+
+    #[test]
+    fn invalid_documents() {
+        let err =
+            HttpApiProblem::from_xml(r#"<problem xmlns="urn:ietf:rfc:7807"><title></problem>"#)
+                .unwrap_err();
+        assert!(matches!(err, ProblemXmlError::Xml(_)));
+
+        let err = with_rfc_mode(RfcMode::Rfc7807, || {
+            HttpApiProblem::from_xml(
+                r#"<problem xmlns="urn:ietf:rfc:7807"><title><a>b</a></title></problem>"#,
+            )
+            .unwrap_err()
+        });
+        assert!(matches!(err, ProblemXmlError::InvalidMember(_)));
+    }
+}
+
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+mod binary {
+    use crate::*;
+    use serde_json::json;
+
+    fn problem() -> HttpApiProblem {
+        with_rfc_mode(RfcMode::Rfc7807, || {
+            HttpApiProblem::new(StatusCode::FORBIDDEN)
+                .type_url("https://example.com/probs/out-of-credit")
+                .title("You do not have enough credit.")
+                .detail("Your current balance is 30, but that costs 50.")
+                .instance("/account/12345/msgs/abc")
+                .value("balance", &30)
+                .value("debt", &-12)
+                .value("ratio", &0.25)
+                .value("huge", &u64::MAX)
+                .value("blocked", &true)
+                .value("nothing", &())
+                .value(
+                    "limits",
+                    &json!({"daily": 50, "nested": {"list": [1, [2, "3"]]}}),
+                )
+        })
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_roundtrip() {
+        let problem = problem();
+
+        let parsed = HttpApiProblem::from_cbor(problem.to_cbor()).unwrap();
+
+        assert_eq!(parsed, problem);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_status_is_an_integer() {
+        let cbor = HttpApiProblem::new(StatusCode::NOT_FOUND).to_cbor();
+
+        let value: ciborium::Value = ciborium::from_reader(cbor.as_slice()).unwrap();
+        let status = value
+            .as_map()
+            .unwrap()
+            .iter()
+            .find(|(k, _)| k.as_text() == Some("status"))
+            .map(|(_, v)| v.clone());
+
+        assert_eq!(status, Some(ciborium::Value::Integer(404.into())));
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_invalid_input() {
+        assert!(HttpApiProblem::from_cbor([0xff, 0x00]).is_err());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_roundtrip() {
+        let problem = problem();
+
+        let parsed = HttpApiProblem::from_msgpack(problem.to_msgpack()).unwrap();
+
+        assert_eq!(parsed, problem);
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_status_is_an_integer() {
+        let msgpack = HttpApiProblem::new(StatusCode::NOT_FOUND).to_msgpack();
+
+        let value: serde_json::Value = rmp_serde::from_slice(&msgpack).unwrap();
+
+        assert_eq!(value["status"], json!(404));
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_invalid_input() {
+        assert!(HttpApiProblem::from_msgpack([0xc1]).is_err());
+    }
+}
+
+mod negotiation {
+    use crate::*;
+
+    #[test]
+    fn defaults_to_problem_json() {
+        assert_eq!(ProblemFormat::negotiate(""), ProblemFormat::ProblemJson);
+        assert_eq!(ProblemFormat::negotiate("*/*"), ProblemFormat::ProblemJson);
+        assert_eq!(
+            ProblemFormat::negotiate("application/*"),
+            ProblemFormat::ProblemJson
+        );
+        assert_eq!(
+            ProblemFormat::negotiate("image/png, image/*"),
+            ProblemFormat::ProblemJson
+        );
+    }
+
+    #[test]
+    fn respects_qualities() {
+        assert_eq!(
+            ProblemFormat::negotiate("application/problem+json;q=0.4, text/plain;q=0.5"),
+            ProblemFormat::PlainText
+        );
+        assert_eq!(
+            ProblemFormat::negotiate("application/json, */*;q=0.9"),
+            ProblemFormat::Json
+        );
+        assert_eq!(
+            ProblemFormat::negotiate(
+                "text/*;q=0.5, application/json;q=0.9, application/problem+json;q=0"
+            ),
+            ProblemFormat::Json
+        );
+        assert_eq!(
+            ProblemFormat::negotiate("text/*, text/html;q=0.1"),
+            ProblemFormat::PlainText
+        );
+    }
+
+    #[test]
+    fn ignores_malformed_ranges() {
+        assert_eq!(
+            ProblemFormat::negotiate("text/plain;q=2, application/json;q=abc, text"),
+            ProblemFormat::ProblemJson
+        );
+        assert_eq!(
+            ProblemFormat::negotiate("Text/HTML ; Q=0.8 ;level=1"),
+            ProblemFormat::Html
+        );
+    }
+
+    #[test]
+    fn from_headers_joins_all_accept_headers() {
+        let mut headers = http::HeaderMap::new();
+        headers.append(http::header::ACCEPT, "text/plain;q=0.2".parse().unwrap());
+        headers.append(http::header::ACCEPT, "text/html;q=0.5".parse().unwrap());
+
+        assert_eq!(ProblemFormat::from_headers(&headers), ProblemFormat::Html);
+        assert_eq!(
+            ProblemFormat::from_headers(&http::HeaderMap::new()),
+            ProblemFormat::ProblemJson
+        );
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn selects_xml() {
+        assert_eq!(
+            ProblemFormat::negotiate("application/problem+xml"),
+            ProblemFormat::ProblemXml
+        );
+        let p = HttpApiProblem::new(StatusCode::NOT_FOUND);
+        assert_eq!(p.format_bytes(ProblemFormat::ProblemXml), p.xml_bytes());
+    }
+}
+
+mod html {
+    use crate::*;
+
+    fn render(problem: &HttpApiProblem) -> String {
+        with_html_template(DefaultHtmlTemplate, || problem.html_string())
+    }
+
+    #[test]
+    fn default_template() {
+        let problem = with_rfc_mode(RfcMode::Rfc7807, || {
+            HttpApiProblem::new(StatusCode::FORBIDDEN)
+                .type_url("https://example.com/probs/out-of-credit")
+                .title("You do not have enough credit.")
+                .detail("Your balance is 30 & that's not enough.")
+                .instance("/account/12345/msgs/abc")
+                .value("balance", &30)
+                .value("accounts", &["/account/12345"])
+                .value("<b>", &"<i>")
+        });
+
+        let html = with_rfc_mode(RfcMode::Rfc7807, || render(&problem));
+
+        assert!(html.contains("<title>You do not have enough credit.</title>"));
+        assert!(html.contains("<p class=\"status\">403 Forbidden</p>"));
+        assert!(html.contains("Your balance is 30 &amp; that&#39;s not enough."));
+        assert!(html
+            .contains("<tr><th>type</th><td><a href=\"https://example.com/probs/out-of-credit\">"));
+        assert!(html.contains("<tr><th>instance</th><td>/account/12345/msgs/abc</td></tr>"));
+        assert!(html.contains("<tr><th>balance</th><td>30</td></tr>"));
+        assert!(html.contains("<tr><th>accounts</th><td>[&quot;/account/12345&quot;]</td></tr>"));
+        assert!(html.contains("<tr><th>&lt;b&gt;</th><td>&lt;i&gt;</td></tr>"));
+        assert!(!html.contains("<b>"));
+    }
+
+    #[test]
+    fn only_links_http_urls() {
+        let html =
+            render(&HttpApiProblem::new(StatusCode::BAD_REQUEST).type_url("javascript:alert(1)"));
+        assert!(!html.contains("<a "));
+        assert!(html.contains("<td>javascript:alert(1)</td>"));
+
+        let html = render(&HttpApiProblem::new(StatusCode::BAD_REQUEST).type_url("about:blank"));
+        assert!(!html.contains("<a "));
+    }
+
+    #[test]
+    fn title_defaults_to_the_reason_phrase() {
+        let html = render(&HttpApiProblem::new(StatusCode::NOT_FOUND));
+
+        assert!(html.contains("<h1>Not Found</h1>"));
+        assert!(!html.contains("<table>"));
+    }
+
+    #[test]
+    fn template_is_used_for_negotiated_html() {
+        let html = with_html_template(
+            |problem: &HttpApiProblem| format!("<p>{}</p>", problem.status.unwrap().as_u16()),
+            || HttpApiProblem::new(StatusCode::GONE).format_string(ProblemFormat::Html),
+        );
+
+        assert_eq!(html, "<p>410</p>");
+        assert!(render(&HttpApiProblem::new(StatusCode::GONE)).starts_with("<!DOCTYPE html>"));
+    }
+}
+
+mod client {
+    use crate::*;
+    use http::Response;
+
+    fn response(status: StatusCode, content_type: &str, body: &str) -> Response<Vec<u8>> {
+        Response::builder()
+            .status(status)
+            .header("content-type", content_type)
+            .body(body.as_bytes().to_vec())
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_problem_json() {
+        let response = response(
+            StatusCode::NOT_FOUND,
+            "application/problem+json",
+            r#"{"type": "https://example.com/probs/gone", "title": "Gone", "status": 404}"#,
+        );
+
+        let prob = HttpApiProblem::from_response(&response);
+
+        assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+        assert_eq!(prob.title.as_deref(), Some("Gone"));
+        assert_eq!(
+            prob.type_url.as_deref(),
+            Some("https://example.com/probs/gone")
+        );
+    }
+
+    #[test]
+    fn transport_status_wins() {
+        let response = response(
+            StatusCode::BAD_GATEWAY,
+            "application/problem+json",
+            r#"{"title": "Gone", "status": 404}"#,
+        );
+
+        let prob = HttpApiProblem::from_response(&response);
+
+        assert_eq!(prob.status, Some(StatusCode::BAD_GATEWAY));
+        assert_eq!(prob.title.as_deref(), Some("Gone"));
+    }
+
+    #[test]
+    fn transport_status_replaces_an_invalid_status() {
+        let response = response(
+            StatusCode::NOT_FOUND,
+            "application/problem+json",
+            r#"{"title": "Gone", "status": "teapot"}"#,
+        );
+
+        let prob = HttpApiProblem::from_response(&response);
+
+        assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+        assert_eq!(prob.raw_status(), None);
+        assert_eq!(serde_json::to_value(&prob).unwrap()["status"], 404);
+    }
+
+    #[test]
+    fn falls_back_to_status_for_other_content() {
+        let response = response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "application/json",
+            r#"{"title": "Gone", "status": 404}"#,
+        );
+
+        let prob = HttpApiProblem::from_response(&response);
+
+        assert_eq!(
+            prob,
+            HttpApiProblem::with_title(StatusCode::SERVICE_UNAVAILABLE)
+        );
+    }
+
+    #[test]
+    fn falls_back_to_status_for_invalid_problem() {
+        let response = response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "application/problem+json",
+            "<html></html>",
+        );
+
+        let prob = HttpApiProblem::from_response(&response);
+
+        assert_eq!(
+            prob,
+            HttpApiProblem::with_title(StatusCode::INTERNAL_SERVER_ERROR)
+        );
+    }
+}
+
+#[cfg(feature = "poem")]
+mod poem {
+    use crate::*;
+    use ::poem::http::header::CONTENT_TYPE;
+    use ::poem::IntoResponse;
+
+    fn problem_of(response: ::poem::Response) -> (StatusCode, HttpApiProblem) {
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON_MEDIA_TYPE
+        );
+        let status = response.status();
+        let body = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(response.into_body().into_bytes())
+            .unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn to_poem_response() {
+        let problem = HttpApiProblem::new(StatusCode::CONFLICT).title("Taken");
+
+        let (status, prob) = problem_of(problem.to_poem_response());
+
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(prob, problem);
+    }
+
+    #[test]
+    fn problems_are_response_errors() {
+        let error = ::poem::Error::from(HttpApiProblem::new(StatusCode::BAD_GATEWAY));
+
+        assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+
+        let (status, prob) = problem_of(error.into_response());
+
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert_eq!(prob.status, Some(StatusCode::BAD_GATEWAY));
+    }
+
+    #[test]
+    fn problems_without_status_are_internal_server_errors() {
+        let (status, _) = problem_of(HttpApiProblem::empty().into_response());
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn api_errors_are_response_errors() {
+        let error = ApiError::builder(StatusCode::NOT_FOUND)
+            .message("no such user")
+            .finish();
+        let error = ::poem::Error::from(error);
+
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+
+        let (status, prob) = problem_of(error.into_response());
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(prob.detail.as_deref(), Some("no such user"));
+    }
+
+    #[cfg(feature = "poem-openapi")]
+    mod openapi {
+        use crate::*;
+        use ::poem_openapi::payload::PlainText;
+        use ::poem_openapi::{ApiResponse, OpenApi, OpenApiService};
+
+        struct Api;
+
+        #[OpenApi]
+        impl Api {
+            #[oai(path = "/users", method = "get")]
+            async fn users(&self) -> Result<PlainText<String>, HttpApiProblem> {
+                Err(HttpApiProblem::new(StatusCode::NOT_FOUND))
+            }
+        }
+
+        #[test]
+        fn the_problem_schema_is_part_of_the_spec() {
+            let spec = OpenApiService::new(Api, "test", "1.0").spec();
+            let spec: serde_json::Value = serde_json::from_str(&spec).unwrap();
+
+            let schema = &spec["components"]["schemas"]["HttpApiProblem"];
+            assert_eq!(schema["type"], "object");
+            assert_eq!(schema["properties"]["status"]["type"], "integer");
+
+            let default = &spec["paths"]["/users"]["get"]["responses"]["default"];
+            assert_eq!(
+                default["content"][PROBLEM_JSON_MEDIA_TYPE]["schema"]["$ref"],
+                "#/components/schemas/HttpApiProblem"
+            );
+        }
+
+        #[test]
+        fn parse_request_errors_become_problems() {
+            let error = ::poem::Error::from_string("missing id", StatusCode::BAD_REQUEST);
+
+            let prob = HttpApiProblem::from_parse_request_error(error);
+
+            assert_eq!(prob.status, Some(StatusCode::BAD_REQUEST));
+            assert_eq!(prob.detail.as_deref(), Some("missing id"));
+        }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+mod reqwest {
+    use crate::*;
+    use hyper::server::conn::http1;
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioIo;
+    use std::convert::Infallible;
+    use tokio::net::TcpListener;
+
+    /// Serves a single request with the response created by `f`
+    async fn stand_in(f: fn() -> http::Response<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let service = service_fn(move |_| async move { Ok::<_, Infallible>(f()) });
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+
+        format!("http://{}/", addr)
+    }
+
+    fn problem_response() -> http::Response<String> {
+        http::Response::builder()
+            .status(StatusCode::CONFLICT)
+            .header("content-type", PROBLEM_JSON_MEDIA_TYPE)
+            .header("x-request-id", "abc")
+            .body(r#"{"title": "Already exists", "detail": "id 42 is taken"}"#.to_string())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn success_is_passed_through() {
+        let url = stand_in(|| http::Response::new("ok".to_string())).await;
+
+        let response = ::reqwest::get(url).await.unwrap();
+        let response = response.error_for_problem().await.unwrap();
+
+        assert_eq!(response.text().await.unwrap(), "ok");
+    }
+
+    #[tokio::test]
+    async fn error_becomes_problem() {
+        let url = stand_in(problem_response).await;
+
+        let response = ::reqwest::get(url).await.unwrap();
+        let prob = response.error_for_problem().await.unwrap_err();
+
+        assert_eq!(prob.status, Some(StatusCode::CONFLICT));
+        assert_eq!(prob.title.as_deref(), Some("Already exists"));
+        assert_eq!(prob.detail.as_deref(), Some("id 42 is taken"));
+    }
+
+    #[tokio::test]
+    async fn error_without_problem_uses_status() {
+        let url = stand_in(|| {
+            http::Response::builder()
+                .status(StatusCode::BAD_GATEWAY)
+                .body("upstream failed".to_string())
+                .unwrap()
+        })
+        .await;
+
+        let response = ::reqwest::get(url).await.unwrap();
+        let prob = response.error_for_problem().await.unwrap_err();
+
+        assert_eq!(prob, HttpApiProblem::with_title(StatusCode::BAD_GATEWAY));
+    }
+
+    #[cfg(feature = "api-error")]
+    #[tokio::test]
+    async fn error_becomes_api_error() {
+        let url = stand_in(problem_response).await;
+
+        let response = ::reqwest::get(url).await.unwrap();
+        let error = response.error_for_api_error().await.unwrap_err();
+
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+        assert_eq!(error.message(), Some("id 42 is taken"));
+        assert_eq!(
+            error.extensions().get::<StatusCode>(),
+            Some(&StatusCode::CONFLICT)
+        );
+        let headers = error.extensions().get::<http::HeaderMap>().unwrap();
+        assert_eq!(headers.get("x-request-id").unwrap(), "abc");
+    }
+}
+
+#[cfg(feature = "api-error")]
+mod derive {
+    #[test]
+    fn reports_invalid_attributes() {
+        trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+    }
+}
+
+mod typed {
+    use crate::*;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use std::convert::TryFrom;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct OutOfCredit {
+        balance: u32,
+    }
+
+    #[test]
+    fn converts_to_untyped() {
+        let typed =
+            TypedProblem::new(StatusCode::FORBIDDEN, OutOfCredit { balance: 30 }).title("foo");
+
+        let prob = HttpApiProblem::try_from(typed).unwrap();
+
+        assert_eq!(prob.title.as_deref(), Some("foo"));
+        assert_eq!(prob.get_value::<&str, u32>("balance"), Some(30));
+    }
+
+    #[test]
+    fn converts_from_untyped() {
+        let prob = HttpApiProblem::new(StatusCode::FORBIDDEN).value("balance", &30);
+
+        let typed: TypedProblem<OutOfCredit> = prob.into_typed().unwrap();
+
+        assert_eq!(typed.status, Some(StatusCode::FORBIDDEN));
+        assert_eq!(typed.extensions, OutOfCredit { balance: 30 });
+    }
+
+    #[test]
+    fn fails_on_type_mismatch() {
+        let prob = HttpApiProblem::new(StatusCode::FORBIDDEN).value("balance", &"thirty");
+
+        assert!(prob.into_typed::<OutOfCredit>().is_err());
+    }
+
+    #[test]
+    fn fails_on_reserved_member_names() {
+        #[derive(Serialize)]
+        struct Reserved {
+            title: u32,
+        }
+
+        let typed = TypedProblem::new(StatusCode::FORBIDDEN, Reserved { title: 1 });
+
+        assert!(serde_json::to_value(typed).is_err());
+    }
+
+    #[test]
+    fn unit_has_no_members() {
+        let typed = TypedProblem::new(StatusCode::NOT_FOUND, ());
+
+        let json = serde_json::to_value(&typed).unwrap();
+        assert_eq!(json, json!({"status": 404}));
+
+        let parsed: TypedProblem<()> = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, typed);
+    }
+}
+
+mod validation {
+    use crate::*;
+    use serde_json::json;
+
+    fn problem() -> HttpApiProblem {
+        ValidationProblem::with_status(StatusCode::UNPROCESSABLE_ENTITY)
+            .pointer("/age", "must be a positive integer")
+            .param(
+                InvalidParam::parameter("color", "must be 'green', 'red' or 'blue'")
+                    .code("enum")
+                    .value("allowed", &["green", "red", "blue"]),
+            )
+            .into()
+    }
+
+    #[test]
+    fn renders_rfc7807_shape() {
+        let json = serde_json::to_value(problem()).unwrap();
+
+        let expected = json!({
+            "status": 422,
+            "title": "Unprocessable Entity",
+            "invalid-params": [
+                {"name": "/age", "reason": "must be a positive integer"},
+                {
+                    "name": "color",
+                    "reason": "must be 'green', 'red' or 'blue'",
+                    "code": "enum",
+                    "allowed": ["green", "red", "blue"]
+                }
+            ]
+        });
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn renders_rfc9457_shape() {
+        let json = with_rfc_mode(RfcMode::Rfc9457, || {
+            serde_json::to_value(problem()).unwrap()
+        });
+
+        let expected = json!({
+            "status": 422,
+            "title": "Unprocessable Entity",
+            "errors": [
+                {"pointer": "#/age", "detail": "must be a positive integer"},
+                {
+                    "parameter": "color",
+                    "detail": "must be 'green', 'red' or 'blue'",
+                    "code": "enum",
+                    "allowed": ["green", "red", "blue"]
+                }
+            ]
+        });
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn reads_both_shapes() {
+        let expected = vec![
+            InvalidParam::pointer("/age", "must be a positive integer"),
+            InvalidParam::parameter("color", "must be 'green', 'red' or 'blue'")
+                .code("enum")
+                .value("allowed", &["green", "red", "blue"]),
+        ];
+
+        assert_eq!(problem().get_invalid_params(), Some(expected.clone()));
+
+        let rfc9457 = with_rfc_mode(RfcMode::Rfc9457, problem);
+        assert_eq!(rfc9457.get_invalid_params(), Some(expected));
+    }
+
+    #[test]
+    fn pointers_are_uri_fragments_with_rfc9457() {
+        let param = InvalidParam::pointer("/first name/ä", "must not be empty");
+
+        let json = with_rfc_mode(RfcMode::Rfc9457, || serde_json::to_value(&param).unwrap());
+        assert_eq!(json["pointer"], json!("#/first%20name/%C3%A4"));
+
+        let parsed: InvalidParam = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, param);
+
+        let parsed: InvalidParam =
+            serde_json::from_value(json!({"name": "#/age", "reason": "too young"})).unwrap();
+        assert_eq!(parsed, InvalidParam::pointer("/age", "too young"));
+    }
+
+    #[test]
+    fn no_params_is_none() {
+        assert_eq!(
+            HttpApiProblem::new(StatusCode::BAD_REQUEST).get_invalid_params(),
+            None
+        );
+    }
+}
+
+#[cfg(feature = "validator")]
+mod validator {
+    use crate::*;
+    use serde_json::json;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Address {
+        #[validate(length(min = 1, message = "must not be empty"))]
+        street: String,
+    }
+
+    #[derive(Validate)]
+    struct Item {
+        #[validate(range(min = 1))]
+        amount: u32,
+    }
+
+    #[derive(Validate)]
+    struct Order {
+        #[validate(email)]
+        email: String,
+        #[validate(nested)]
+        address: Address,
+        #[validate(nested)]
+        items: Vec<Item>,
+    }
+
+    fn invalid_order() -> Order {
+        Order {
+            email: "nope".into(),
+            address: Address {
+                street: String::new(),
+            },
+            items: vec![Item { amount: 1 }, Item { amount: 0 }],
+        }
+    }
+
+    #[test]
+    fn flattens_errors_to_pointers() {
+        let errors = invalid_order().validate().unwrap_err();
+
+        let prob = HttpApiProblem::from(errors);
+
+        assert_eq!(prob.status, Some(StatusCode::UNPROCESSABLE_ENTITY));
+
+        let params = prob.get_invalid_params().unwrap();
+        let pointers: Vec<_> = params.iter().map(|p| p.location.as_str()).collect();
+        assert_eq!(
+            pointers,
+            vec!["/address/street", "/email", "/items/1/amount"]
+        );
+
+        assert_eq!(params[0].reason, "must not be empty");
+        assert_eq!(params[0].code.as_deref(), Some("length"));
+        assert_eq!(params[1].reason, "email");
+        assert_eq!(params[2].values["params"]["min"], json!(1));
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn converts_into_api_error() {
+        let errors = invalid_order().validate().unwrap_err();
+
+        let error = ApiError::from(errors);
+
+        assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(error.fields().contains_key(INVALID_PARAMS_MEMBER));
+    }
+}
+
+#[cfg(feature = "garde")]
+mod garde {
+    use crate::*;
+    use garde::Validate;
+
+    #[derive(Validate)]
+    struct Item {
+        #[garde(range(min = 1))]
+        amount: u32,
+    }
+
+    #[derive(Validate)]
+    struct Order {
+        #[garde(length(min = 1))]
+        name: String,
+        #[garde(dive)]
+        items: Vec<Item>,
+    }
+
+    fn report() -> garde::Report {
+        Order {
+            name: String::new(),
+            items: vec![Item { amount: 1 }, Item { amount: 0 }],
+        }
+        .validate()
+        .unwrap_err()
+    }
+
+    #[test]
+    fn maps_paths_to_pointers() {
+        let prob = HttpApiProblem::from(report());
+
+        assert_eq!(prob.status, Some(StatusCode::UNPROCESSABLE_ENTITY));
+
+        let params = prob.get_invalid_params().unwrap();
+        let pointers: Vec<_> = params.iter().map(|p| p.location.as_str()).collect();
+        assert_eq!(pointers, vec!["/items/1/amount", "/name"]);
+    }
+
+    #[test]
+    fn escapes_pointer_segments() {
+        let mut report = garde::Report::new();
+        report.append(
+            garde::Path::new("a/b").join("c~d"),
+            garde::Error::new("invalid"),
+        );
+
+        let prob = ValidationProblem::from(report);
+
+        assert_eq!(prob.params()[0].location.as_str(), "/a~1b/c~0d");
+    }
+
+    #[test]
+    fn maps_indices_to_pointer_segments() {
+        let mut report = garde::Report::new();
+        report.append(
+            garde::Path::new(2).join(0).join("amount"),
+            garde::Error::new("invalid"),
+        );
+
+        let prob = ValidationProblem::from(report);
+
+        assert_eq!(prob.params()[0].location.as_str(), "/2/0/amount");
+    }
+
+    #[test]
+    fn keeps_keys_containing_separators() {
+        let mut report = garde::Report::new();
+        report.append(
+            garde::Path::new("by_addr")
+                .join("ann.smith@example.com")
+                .join("a/b[0]"),
+            garde::Error::new("invalid"),
+        );
+
+        let prob = ValidationProblem::from(report);
+
+        assert_eq!(
+            prob.params()[0].location.as_str(),
+            "/by_addr/ann.smith@example.com/a~1b[0]"
+        );
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn converts_into_api_error() {
+        let error = ApiError::from(report());
+
+        assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(error.fields().contains_key(INVALID_PARAMS_MEMBER));
+    }
+}
+
+mod registry {
+    use crate::*;
+
+    const OUT_OF_CREDIT: &str = "https://example.com/probs/out-of-credit";
+
+    fn registry() -> ProblemTypeRegistry {
+        ProblemTypeRegistry::new().with(
+            ProblemType::new(
+                OUT_OF_CREDIT,
+                "You do not have enough credit.",
+                StatusCode::FORBIDDEN,
+            )
+            .extension_member("balance"),
+        )
+    }
+
+    #[test]
+    fn creates_problems_of_registered_types() {
+        let prob = registry().problem(OUT_OF_CREDIT).unwrap();
+
+        assert_eq!(prob.status, Some(StatusCode::FORBIDDEN));
+        assert_eq!(prob.type_url.as_deref(), Some(OUT_OF_CREDIT));
+        assert_eq!(
+            prob.title.as_deref(),
+            Some("You do not have enough credit.")
+        );
+    }
+
+    #[test]
+    fn rejects_unregistered_types() {
+        assert_eq!(
+            registry().problem("https://example.com/probs/unknown"),
+            Err(ProblemTypeError::Unregistered(
+                "https://example.com/probs/unknown".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn checks_extension_members() {
+        let registry = registry();
+
+        let prob = registry
+            .problem(OUT_OF_CREDIT)
+            .unwrap()
+            .value("balance", &30);
+        assert_eq!(registry.check(&prob), Ok(()));
+
+        let prob = prob.value("accounts", &["/account/12345"]);
+        assert_eq!(
+            registry.check(&prob),
+            Err(ProblemTypeError::UndeclaredMember {
+                type_url: OUT_OF_CREDIT.to_string(),
+                member: "accounts".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn accepts_problems_without_type() {
+        let registry = registry();
+
+        assert_eq!(
+            registry.check(&HttpApiProblem::new(StatusCode::NOT_FOUND)),
+            Ok(())
+        );
+        assert_eq!(
+            registry.check(&HttpApiProblem::new(StatusCode::NOT_FOUND).type_url(ABOUT_BLANK)),
+            Ok(())
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "is not registered")]
+    fn debug_check_panics_in_debug_builds() {
+        registry().debug_check(
+            &HttpApiProblem::new(StatusCode::NOT_FOUND)
+                .type_url("https://example.com/probs/unknown"),
+        );
+    }
+}
+
+mod default_type_url {
+    use crate::*;
+
+    #[test]
+    fn follows_the_rfc_mode_if_not_configured() {
+        let prob = with_rfc_mode(RfcMode::Rfc7807, || {
+            HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+        });
+        assert_eq!(
+            prob.type_url.as_deref(),
+            Some("https://httpstatuses.com/404")
+        );
+
+        let prob = with_rfc_mode(RfcMode::Rfc9457, || {
+            HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND)
+        });
+        assert_eq!(prob.type_url.as_deref(), Some(ABOUT_BLANK));
+    }
+
+    #[test]
+    fn uses_the_configured_default() {
+        let base = DefaultTypeUrl::BaseUrl("https://docs.example.com/problems/".to_string());
+
+        let prob = with_rfc_mode(RfcMode::Rfc9457, || {
+            with_default_type_url(base, || {
+                HttpApiProblem::with_title_and_type(StatusCode::CONFLICT)
+            })
+        });
+
+        assert_eq!(
+            prob.type_url.as_deref(),
+            Some("https://docs.example.com/problems/409")
+        );
+        assert_eq!(prob.title.as_deref(), Some("Conflict"));
+    }
+
+    #[test]
+    fn none_sets_no_type() {
+        let prob = with_default_type_url(DefaultTypeUrl::None, || {
+            HttpApiProblem::with_title_and_type(StatusCode::CONFLICT)
+        });
+
+        assert_eq!(prob.type_url, None);
+        assert_eq!(prob.title.as_deref(), Some("Conflict"));
+    }
+
+    #[test]
+    fn mdn_points_to_the_status_reference() {
+        assert_eq!(
+            DefaultTypeUrl::Mdn
+                .type_url(StatusCode::IM_A_TEAPOT)
+                .as_deref(),
+            Some("https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Status/418")
+        );
+    }
+
+    #[test]
+    fn registry_default_overrides_the_process_default() {
+        let registry = ProblemTypeRegistry::new().default_type_url(DefaultTypeUrl::AboutBlank);
+
+        let prob = with_rfc_mode(RfcMode::Rfc7807, || {
+            registry.status_problem(StatusCode::NOT_FOUND)
+        });
+
+        assert_eq!(prob.type_url.as_deref(), Some(ABOUT_BLANK));
+    }
+
+    #[test]
+    fn registry_accepts_its_default_types() {
+        let registry = ProblemTypeRegistry::new().default_type_url(DefaultTypeUrl::BaseUrl(
+            "https://docs.example.com".to_string(),
+        ));
+
+        let prob = registry.status_problem(StatusCode::NOT_FOUND);
+        assert!(registry.check(&prob).is_ok());
+
+        let prob = prob.status(StatusCode::CONFLICT);
+        assert!(registry.check(&prob).is_err());
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn api_errors_use_the_default() {
+        let prob = with_default_type_url(DefaultTypeUrl::Mdn, || {
+            ApiError::new(StatusCode::NOT_FOUND).into_http_api_problem()
+        });
+
+        assert_eq!(
+            prob.type_url.as_deref(),
+            Some("https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Status/404")
+        );
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn borrowed_api_errors_use_the_default() {
+        let error = ApiError::new(StatusCode::NOT_FOUND);
+
+        let prob = with_default_type_url(DefaultTypeUrl::Mdn, || error.to_http_api_problem());
+
+        assert_eq!(
+            prob.type_url.as_deref(),
+            Some("https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Status/404")
+        );
+        assert_eq!(prob.title.as_deref(), Some("Not Found"));
+    }
+}
+
+#[cfg(feature = "warp")]
+mod warp {
+    use crate::*;
+    use ::warp::Filter;
+
+    async fn problem_for(request: ::warp::test::RequestBuilder) -> (u16, HttpApiProblem) {
+        let routes = ::warp::path("teapot")
+            .and(::warp::get())
+            .and_then(|| async {
+                Err::<String, _>(::warp::reject::custom(
+                    HttpApiProblem::new(StatusCode::IM_A_TEAPOT).title("I'm a teapot"),
+                ))
+            })
+            .or(::warp::path("json")
+                .and(::warp::post())
+                .and(::warp::body::json())
+                .map(|body: serde_json::Value| body.to_string()))
+            .recover(recover_warp_rejection);
+
+        let response = request.reply(&routes).await;
+
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON_MEDIA_TYPE);
+
+        (
+            response.status().as_u16(),
+            serde_json::from_slice(response.body()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn renders_custom_problems() {
+        let (status, prob) = problem_for(::warp::test::request().path("/teapot")).await;
+
+        assert_eq!(status, 418);
+        assert_eq!(prob.status, Some(StatusCode::IM_A_TEAPOT));
+        assert_eq!(prob.title.as_deref(), Some("I'm a teapot"));
+    }
+
+    #[tokio::test]
+    async fn maps_not_found() {
+        let (status, prob) = problem_for(::warp::test::request().path("/nothing")).await;
+
+        assert_eq!(status, 404);
+        assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn maps_method_not_allowed() {
+        let (status, _) =
+            problem_for(::warp::test::request().method("DELETE").path("/teapot")).await;
+
+        assert_eq!(status, 405);
+    }
+
+    #[tokio::test]
+    async fn maps_body_deserialize_errors() {
+        let (status, prob) = problem_for(
+            ::warp::test::request()
+                .method("POST")
+                .path("/json")
+                .header("content-type", "application/json")
+                .body("{"),
+        )
+        .await;
+
+        assert_eq!(status, 400);
+        assert!(prob.detail.is_some());
+    }
+
+    #[test]
+    fn to_warp_response_sets_status_and_headers() {
+        let prob = HttpApiProblem::new(StatusCode::CONFLICT).title("Conflict");
+        let json = prob.json_string();
+
+        let response = ::warp::Reply::into_response(prob);
+
+        assert_eq!(response.status().as_u16(), 409);
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON_MEDIA_TYPE);
+        assert_eq!(
+            response.headers()["content-length"],
+            json.len().to_string().as_str()
+        );
+    }
+
+    #[test]
+    fn missing_status_becomes_internal_server_error() {
+        let response = into_warp_response(HttpApiProblem::empty());
+
+        assert_eq!(response.status().as_u16(), 500);
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn api_errors_are_replies() {
+        let response = ::warp::Reply::into_response(ApiError::new(StatusCode::CONFLICT));
+
+        assert_eq!(response.status().as_u16(), 409);
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn negotiates_the_format_of_rejections() {
+        let routes = negotiate_warp_rejections(
+            ::warp::path("teapot")
+                .and_then(|| async {
+                    Err::<String, _>(::warp::reject::custom(HttpApiProblem::new(
+                        StatusCode::IM_A_TEAPOT,
+                    )))
+                })
+                .or(::warp::path("hello").map(|| "hello")),
+        );
+
+        let response = ::warp::test::request()
+            .path("/teapot")
+            .header("accept", "text/plain")
+            .reply(&routes)
+            .await;
+
+        assert_eq!(response.status().as_u16(), 418);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(response.body().as_ref(), b"418 I'm a teapot");
+
+        let response = ::warp::test::request()
+            .path("/nothing")
+            .reply(&routes)
+            .await;
+
+        assert_eq!(response.status().as_u16(), 404);
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON_MEDIA_TYPE);
+
+        let response = ::warp::test::request()
+            .path("/hello")
+            .header("accept", "text/html")
+            .reply(&routes)
+            .await;
+
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(response.body().as_ref(), b"hello");
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn renders_api_errors() {
+        let rejection = ::warp::reject::custom(ApiError::new(StatusCode::CONFLICT));
+
+        let prob = HttpApiProblem::from_warp_rejection(&rejection);
+
+        assert_eq!(prob.status, Some(StatusCode::CONFLICT));
+    }
+}
+
+#[cfg(feature = "axum")]
+mod axum {
+    use crate::*;
+    use axum_core::body::Body;
+    use axum_crate::routing::{get, post};
+    use axum_crate::Router;
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize)]
+    struct NewUser {
+        name: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Paging {
+        page: u32,
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/users",
+                post(|ProblemJson(user): ProblemJson<NewUser>| async move { user.name }),
+            )
+            .route(
+                "/users/{id}",
+                get(|ProblemPath(id): ProblemPath<u32>| async move { id.to_string() }),
+            )
+            .route(
+                "/pages",
+                get(|ProblemQuery(paging): ProblemQuery<Paging>| async move {
+                    paging.page.to_string()
+                }),
+            )
+            .route(
+                "/forms",
+                post(|ProblemForm(user): ProblemForm<NewUser>| async move { user.name }),
+            )
+    }
+
+    async fn problem_for(request: http::Request<Body>) -> HttpApiProblem {
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON_MEDIA_TYPE);
+
+        let status = response.status();
+        let body = axum_crate::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let prob: HttpApiProblem = serde_json::from_slice(&body).unwrap();
+        assert_eq!(prob.status, Some(status));
+        prob
+    }
+
+    fn json_request(content_type: &str, body: &str) -> http::Request<Body> {
+        http::Request::post("/users")
+            .header("content-type", content_type)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn extracts_valid_requests() {
+        let response = app()
+            .oneshot(json_request("application/json", r#"{"name": "Ann"}"#))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn json_syntax_errors_are_bad_requests() {
+        let prob = problem_for(json_request("application/json", "{")).await;
+
+        assert_eq!(prob.status, Some(StatusCode::BAD_REQUEST));
+        assert!(prob.detail.is_some());
+    }
+
+    #[tokio::test]
+    async fn json_data_errors_are_unprocessable() {
+        let prob = problem_for(json_request("application/json", r#"{"name": 1}"#)).await;
+
+        assert_eq!(prob.status, Some(StatusCode::UNPROCESSABLE_ENTITY));
+    }
+
+    #[tokio::test]
+    async fn missing_json_content_type_is_unsupported() {
+        let prob = problem_for(json_request("text/plain", r#"{"name": "Ann"}"#)).await;
+
+        assert_eq!(prob.status, Some(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+    }
+
+    #[tokio::test]
+    async fn invalid_path_params_are_bad_requests() {
+        let prob = problem_for(
+            http::Request::get("/users/ann")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(prob.status, Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn invalid_queries_are_bad_requests() {
+        let prob = problem_for(
+            http::Request::get("/pages?page=first")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(prob.status, Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn invalid_forms_are_unprocessable() {
+        let prob = problem_for(
+            http::Request::post("/forms")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from("nickname=ann"))
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(prob.status, Some(StatusCode::UNPROCESSABLE_ENTITY));
+    }
+
+    #[cfg(feature = "api-error")]
+    #[tokio::test]
+    async fn rejections_convert_into_api_errors() {
+        use axum_crate::extract::rejection::JsonRejection;
+        use axum_crate::extract::FromRequest;
+
+        let rejection: JsonRejection = axum_crate::Json::<serde_json::Value>::from_request(
+            json_request("text/plain", "{}"),
+            &(),
+        )
+        .await
+        .unwrap_err();
+
+        let error = ApiError::from(rejection);
+
+        assert_eq!(error.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert!(error.message().is_some());
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[cfg(feature = "tower")]
+    #[tokio::test]
+    async fn rejections_are_negotiated_by_the_problem_layer() {
+        let request = http::Request::get("/users/abc")
+            .header("accept", "text/html")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app()
+            .layer(ProblemLayer::new())
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/html; charset=utf-8"
+        );
+        let body = axum_crate::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("<h1>Bad Request</h1>"));
+    }
+}
+
+#[cfg(feature = "actix-web")]
+mod actix {
+    use crate::*;
+    use actix_web_crate::test::{self as actix_test, TestRequest};
+    use actix_web_crate::{web, App};
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct NewUser {
+        name: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Paging {
+        page: u32,
+    }
+
+    fn problem_for(request: TestRequest) -> HttpApiProblem {
+        actix_web_crate::rt::System::new().block_on(async {
+            let app = actix_test::init_service(
+                App::new()
+                    .app_data(
+                        web::JsonConfig::default()
+                            .limit(32)
+                            .error_handler(actix_json_error_handler),
+                    )
+                    .app_data(web::PathConfig::default().error_handler(actix_path_error_handler))
+                    .app_data(web::QueryConfig::default().error_handler(actix_query_error_handler))
+                    .app_data(web::FormConfig::default().error_handler(actix_form_error_handler))
+                    .route(
+                        "/users",
+                        web::post().to(|user: web::Json<NewUser>| async move { user.name.clone() }),
+                    )
+                    .route(
+                        "/users/{id}",
+                        web::get().to(|id: web::Path<u32>| async move { id.to_string() }),
+                    )
+                    .route(
+                        "/pages",
+                        web::get().to(|paging: web::Query<Paging>| async move {
+                            paging.page.to_string()
+                        }),
+                    )
+                    .route(
+                        "/forms",
+                        web::post().to(|user: web::Form<NewUser>| async move { user.name.clone() }),
+                    ),
+            )
+            .await;
+
+            let response = actix_test::call_service(&app, request.to_request()).await;
+
+            assert_eq!(
+                response.headers().get("content-type").unwrap(),
+                PROBLEM_JSON_MEDIA_TYPE
+            );
+
+            let status = response.status().as_u16();
+            let prob: HttpApiProblem = actix_test::read_body_json(response).await;
+            assert_eq!(prob.status.map(|s| s.as_u16()), Some(status));
+            prob
+        })
+    }
+
+    fn json_request(content_type: &str, body: &'static str) -> TestRequest {
+        TestRequest::post()
+            .uri("/users")
+            .insert_header(("content-type", content_type.to_string()))
+            .set_payload(body)
+    }
+
+    #[test]
+    fn json_syntax_errors_are_bad_requests() {
+        let prob = problem_for(json_request("application/json", "{"));
+
+        assert_eq!(prob.status, Some(StatusCode::BAD_REQUEST));
+        assert!(prob.detail.is_some());
+    }
+
+    #[test]
+    fn json_data_errors_are_unprocessable() {
+        let prob = problem_for(json_request("application/json", r#"{"name": 1}"#));
+
+        assert_eq!(prob.status, Some(StatusCode::UNPROCESSABLE_ENTITY));
+    }
+
+    #[test]
+    fn json_content_type_errors_are_unsupported() {
+        let prob = problem_for(json_request("text/plain", r#"{"name": "Ann"}"#));
+
+        assert_eq!(prob.status, Some(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+    }
+
+    #[test]
+    fn json_overflows_are_too_large() {
+        let prob = problem_for(json_request(
+            "application/json",
+            r#"{"name": "Ann Annabel Annette Annika"}"#,
+        ));
+
+        assert_eq!(prob.status, Some(StatusCode::PAYLOAD_TOO_LARGE));
+    }
+
+    #[test]
+    fn path_errors_are_bad_requests() {
+        let prob = problem_for(TestRequest::get().uri("/users/ann"));
+
+        assert_eq!(prob.status, Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn query_errors_are_bad_requests() {
+        let prob = problem_for(TestRequest::get().uri("/pages?page=first"));
+
+        assert_eq!(prob.status, Some(StatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn form_errors_are_unprocessable() {
+        let prob = problem_for(
+            TestRequest::post()
+                .uri("/forms")
+                .insert_header(("content-type", "application/x-www-form-urlencoded"))
+                .set_payload("nickname=ann"),
+        );
+
+        assert_eq!(prob.status, Some(StatusCode::UNPROCESSABLE_ENTITY));
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn errors_convert_into_api_errors() {
+        use actix_web_crate::error::JsonPayloadError;
+
+        let error = ApiError::from(JsonPayloadError::ContentType);
+
+        assert_eq!(error.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn negotiates_the_format() {
+        actix_web_crate::rt::System::new().block_on(async {
+            let app = actix_test::init_service(
+                App::new()
+                    .app_data(web::PathConfig::default().error_handler(actix_path_error_handler))
+                    .route(
+                        "/users/{id}",
+                        web::get().to(|id: web::Path<u32>| async move { id.to_string() }),
+                    ),
+            )
+            .await;
+
+            let request = TestRequest::get()
+                .uri("/users/abc")
+                .insert_header(("accept", "application/json"))
+                .to_request();
+            let response = actix_test::call_service(&app, request).await;
+
+            assert_eq!(response.status().as_u16(), 400);
+            assert_eq!(
+                response.headers().get("content-type").unwrap(),
+                "application/json"
+            );
+            let prob: HttpApiProblem = actix_test::read_body_json(response).await;
+            assert_eq!(prob.status, Some(StatusCode::BAD_REQUEST));
+        })
+    }
+}
+
+#[cfg(feature = "rocket")]
+mod rocket {
+    use crate::*;
+    use ::rocket::http::ContentType;
+    use ::rocket::local::blocking::Client;
+    use ::rocket::{get, routes};
+
+    #[get("/users/<id>")]
+    fn user(id: u32) -> String {
+        id.to_string()
+    }
+
+    #[get("/panic")]
+    fn panics() -> String {
+        panic!("handler panicked")
+    }
+
+    fn problem_for(uri: &str) -> (u16, HttpApiProblem) {
+        let rocket = ::rocket::build()
+            .mount("/", routes![user, panics])
+            .register("/", rocket_catchers());
+        let client = Client::tracked(rocket).unwrap();
+
+        let response = client.get(uri).dispatch();
+
+        assert_eq!(
+            response.content_type(),
+            Some(ContentType::new("application", "problem+json"))
+        );
+
+        let status = response.status().code;
+        let body = response.into_bytes().unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn renders_not_found() {
+        let (status, prob) = problem_for("/nothing?at=all");
+
+        assert_eq!(status, 404);
+        assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+        assert_eq!(prob.title.as_deref(), Some("Not Found"));
+        assert_eq!(prob.instance.as_deref(), Some("/nothing?at=all"));
+    }
+
+    #[test]
+    fn renders_guard_failures() {
+        let (status, prob) = problem_for("/users/ann");
+
+        assert_eq!(prob.status.map(|s| s.as_u16()), Some(status));
+        assert!(StatusCode::from_u16(status).unwrap().is_client_error());
+        assert_eq!(prob.instance.as_deref(), Some("/users/ann"));
+    }
+
+    #[test]
+    fn renders_panics() {
+        let (status, prob) = problem_for("/panic");
+
+        assert_eq!(status, 500);
+        assert_eq!(prob.status, Some(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn negotiates_the_format() {
+        let rocket = ::rocket::build().register("/", rocket_catchers());
+        let client = Client::tracked(rocket).unwrap();
+
+        let response = client
+            .get("/nothing")
+            .header(::rocket::http::Header::new("Accept", "text/plain"))
+            .dispatch();
+
+        assert_eq!(response.status().code, 404);
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+        assert_eq!(response.into_string().unwrap(), "404 Not Found - Not Found");
+    }
+}
+
+#[cfg(feature = "salvo")]
+mod salvo {
+    use crate::*;
+    use ::salvo::http::{ResBody, StatusError};
+    use ::salvo::{Depot, FlowCtrl, Handler, Request, Response, Scribe};
+
+    fn problem_of(res: &mut Response) -> HttpApiProblem {
+        assert_eq!(res.headers()["content-type"], PROBLEM_JSON_MEDIA_TYPE);
+
+        match res.take_body() {
+            ResBody::Once(bytes) => serde_json::from_slice(&bytes).unwrap(),
+            body => panic!("unexpected body {:?}", body),
+        }
+    }
+
+    fn catch(res: &mut Response) -> HttpApiProblem {
+        let mut req = Request::new();
+        req.set_uri("http://localhost/users/1?full=true".parse().unwrap());
+
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(SalvoProblemCatcher.handle(
+                &mut req,
+                &mut Depot::new(),
+                res,
+                &mut FlowCtrl::new(vec![]),
+            ));
+
+        problem_of(res)
+    }
+
+    #[test]
+    fn renders_problems() {
+        let mut res = Response::new();
+
+        HttpApiProblem::new(StatusCode::CONFLICT)
+            .title("Conflict")
+            .render(&mut res);
+
+        assert_eq!(res.status_code, Some(StatusCode::CONFLICT));
+        assert_eq!(problem_of(&mut res).title.as_deref(), Some("Conflict"));
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn renders_api_errors() {
+        let mut res = Response::new();
+
+        ApiError::new(StatusCode::CONFLICT).render(&mut res);
+
+        assert_eq!(res.status_code, Some(StatusCode::CONFLICT));
+        assert_eq!(problem_of(&mut res).status, Some(StatusCode::CONFLICT));
+    }
+
+    #[test]
+    fn catcher_renders_status() {
+        let mut res = Response::new();
+        res.status_code(StatusCode::NOT_FOUND);
+
+        let prob = catch(&mut res);
+
+        assert_eq!(res.status_code, Some(StatusCode::NOT_FOUND));
+        assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+        assert_eq!(prob.instance.as_deref(), Some("/users/1?full=true"));
+    }
+
+    #[test]
+    fn catcher_renders_status_errors() {
+        let mut res = Response::new();
+        res.render(StatusError::bad_request().detail("the id must be a number"));
+
+        let prob = catch(&mut res);
+
+        assert_eq!(res.status_code, Some(StatusCode::BAD_REQUEST));
+        assert_eq!(prob.title.as_deref(), Some("Bad Request"));
+        assert_eq!(prob.detail.as_deref(), Some("the id must be a number"));
+    }
+
+    #[test]
+    fn catcher_ignores_successes() {
+        let mut res = Response::new();
+        res.status_code(StatusCode::OK);
+        res.body("fine");
+
+        let mut req = Request::new();
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(SalvoProblemCatcher.handle(
+                &mut req,
+                &mut Depot::new(),
+                &mut res,
+                &mut FlowCtrl::new(vec![]),
+            ));
+
+        assert!(res.headers().get("content-type").is_none());
+    }
+
+    #[test]
+    fn catcher_negotiates_the_format() {
+        let mut res = Response::new();
+        res.status_code(StatusCode::NOT_FOUND);
+
+        let mut req = Request::new();
+        req.headers_mut()
+            .insert("accept", "application/json".parse().unwrap());
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(SalvoProblemCatcher.handle(
+                &mut req,
+                &mut Depot::new(),
+                &mut res,
+                &mut FlowCtrl::new(vec![]),
+            ));
+
+        assert_eq!(res.headers()["content-type"], "application/json");
+        match res.take_body() {
+            ResBody::Once(bytes) => {
+                let prob: HttpApiProblem = serde_json::from_slice(&bytes).unwrap();
+                assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+            }
+            body => panic!("unexpected body {:?}", body),
+        }
+    }
+}
+
+#[cfg(feature = "tide")]
+mod tide {
+    use crate::*;
+    use ::tide::http::{Method, Request, Url};
+
+    fn problem_for(path: &str) -> (u16, Option<HttpApiProblem>) {
+        let mut app = ::tide::new();
+        app.with(TideProblemMiddleware);
+        app.at("/problem").get(|_| async {
+            Err::<String, _>(::tide::Error::new(
+                409,
+                HttpApiProblem::new(StatusCode::CONFLICT).title("Taken"),
+            ))
+        });
+        app.at("/other")
+            .get(|_| async { Err::<String, _>(::tide::Error::from_str(503, "database is down")) });
+        app.at("/fine").get(|_| async { Ok("fine") });
+        app.at("/body")
+            .get(|_| async { Ok(::tide::Response::builder(400).body("plain").build()) });
+        app.at("/stream").get(|_| async {
+            let stream =
+                ::tide::Body::from_reader(::tide::Body::from_string("streamed".into()), None);
+            Ok(::tide::Response::builder(500).body(stream).build())
+        });
+
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let req = Request::new(
+                Method::Get,
+                Url::parse("http://localhost").unwrap().join(path).unwrap(),
+            );
+            let mut res: ::tide::http::Response = app.respond(req).await.unwrap();
+
+            let status = u16::from(res.status());
+            if res.content_type().map(|m| m.essence().to_string())
+                != Some(PROBLEM_JSON_MEDIA_TYPE.to_string())
+            {
+                return (status, None);
+            }
+
+            let body = res.body_bytes().await.unwrap();
+            (status, Some(serde_json::from_slice(&body).unwrap()))
+        })
+    }
+
+    #[test]
+    fn renders_problems_carried_by_errors() {
+        let (status, prob) = problem_for("/problem");
+        let prob = prob.unwrap();
+
+        assert_eq!(status, 409);
+        assert_eq!(prob.title.as_deref(), Some("Taken"));
+    }
+
+    #[test]
+    fn renders_other_errors_from_their_status() {
+        let (status, prob) = problem_for("/other");
+        let prob = prob.unwrap();
+
+        assert_eq!(status, 503);
+        assert_eq!(prob.status, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(prob.detail, None);
+    }
+
+    #[test]
+    fn renders_empty_error_responses() {
+        let (status, prob) = problem_for("/nothing");
+
+        assert_eq!(status, 404);
+        assert_eq!(prob.unwrap().status, Some(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn keeps_other_responses() {
+        assert_eq!(problem_for("/fine"), (200, None));
+        assert_eq!(problem_for("/body"), (400, None));
+        assert_eq!(problem_for("/stream"), (500, None));
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn renders_api_errors() {
+        let mut app = ::tide::new();
+        app.with(TideProblemMiddleware);
+        app.at("/").get(|_| async {
+            Err::<String, _>(::tide::Error::new(
+                422,
+                ApiError::builder(StatusCode::UNPROCESSABLE_ENTITY)
+                    .message("name is missing")
+                    .finish(),
+            ))
+        });
+
+        let prob: HttpApiProblem = tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let req = Request::new(Method::Get, Url::parse("http://localhost/").unwrap());
+            let mut res: ::tide::http::Response = app.respond(req).await.unwrap();
+            serde_json::from_slice(&res.body_bytes().await.unwrap()).unwrap()
+        });
+
+        assert_eq!(prob.detail.as_deref(), Some("name is missing"));
+    }
+
+    #[test]
+    fn negotiates_the_format() {
+        let mut app = ::tide::new();
+        app.with(TideProblemMiddleware);
+
+        let (content_type, body) = tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let mut req = Request::new(Method::Get, Url::parse("http://localhost/").unwrap());
+            req.insert_header("Accept", "text/html;q=0.9, text/plain");
+            let mut res: ::tide::http::Response = app.respond(req).await.unwrap();
+            (
+                res.content_type().unwrap().to_string(),
+                res.body_string().await.unwrap(),
+            )
+        });
+
+        assert_eq!(content_type, "text/plain;charset=utf-8");
+        assert_eq!(body, "404 Not Found - Not Found");
+    }
+
+    #[test]
+    fn keeps_the_headers_of_the_response() {
+        let mut app = ::tide::new();
+        app.with(TideProblemMiddleware);
+        app.at("/").get(|_| async {
+            let error = ::tide::Error::from_str(405, "use POST");
+            let mut res = ::tide::Response::new(405);
+            res.insert_header("Allow", "POST");
+            res.set_error(error);
+            Ok(res)
+        });
+
+        let (allow, content_type, prob) = tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let req = Request::new(Method::Get, Url::parse("http://localhost/").unwrap());
+            let mut res: ::tide::http::Response = app.respond(req).await.unwrap();
+            (
+                res.header("Allow").map(|v| v.as_str().to_string()),
+                res.content_type().unwrap().to_string(),
+                serde_json::from_slice::<HttpApiProblem>(&res.body_bytes().await.unwrap()).unwrap(),
+            )
+        });
+
+        assert_eq!(allow.as_deref(), Some("POST"));
+        assert_eq!(content_type, PROBLEM_JSON_MEDIA_TYPE);
+        assert_eq!(prob.status, Some(StatusCode::METHOD_NOT_ALLOWED));
+    }
+}
+
+#[cfg(feature = "tonic")]
+mod tonic {
+    use crate::*;
+    use ::tonic::metadata::MetadataValue;
+    use ::tonic::{Code, Status};
+
+    #[test]
+    fn grpc_codes_are_mapped_to_statuses() {
+        assert_eq!(
+            grpc_code_to_status_code(Code::InvalidArgument),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            grpc_code_to_status_code(Code::DeadlineExceeded),
+            StatusCode::GATEWAY_TIMEOUT
+        );
+        assert_eq!(
+            grpc_code_to_status_code(Code::Unauthenticated),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(grpc_code_to_status_code(Code::Cancelled).as_u16(), 499);
+    }
+
+    #[test]
+    fn statuses_are_mapped_to_grpc_codes() {
+        for code in [
+            Code::Cancelled,
+            Code::InvalidArgument,
+            Code::DeadlineExceeded,
+            Code::NotFound,
+            Code::AlreadyExists,
+            Code::PermissionDenied,
+            Code::ResourceExhausted,
+            Code::Unimplemented,
+            Code::Unavailable,
+            Code::Unauthenticated,
+        ] {
+            assert_eq!(
+                status_code_to_grpc_code(grpc_code_to_status_code(code)),
+                code
+            );
+        }
+
+        assert_eq!(
+            status_code_to_grpc_code(StatusCode::BAD_GATEWAY),
+            Code::Internal
+        );
+        assert_eq!(
+            status_code_to_grpc_code(StatusCode::PERMANENT_REDIRECT),
+            Code::Unknown
+        );
+    }
+
+    #[test]
+    fn status_to_problem() {
+        let mut status = Status::new(Code::NotFound, "no such user");
+        status
+            .metadata_mut()
+            .insert("x-request-id", MetadataValue::from_static("abc"));
+        status
+            .metadata_mut()
+            .append("x-shard", MetadataValue::from_static("1"));
+        status
+            .metadata_mut()
+            .append("x-shard", MetadataValue::from_static("2"));
+
+        let prob = HttpApiProblem::from(status);
+
+        assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+        assert_eq!(prob.title.as_deref(), Some("Not Found"));
+        assert_eq!(prob.detail.as_deref(), Some("no such user"));
+        assert_eq!(
+            prob.get_value::<String, String>("x-request-id").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            prob.get_value::<String, Vec<String>>("x-shard"),
+            Some(vec!["1".to_string(), "2".to_string()])
+        );
+    }
+
+    #[test]
+    fn transport_headers_are_not_copied() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert("grpc-status", "5".parse().unwrap());
+        headers.insert("grpc-message", "no%20such%20user".parse().unwrap());
+        headers.insert("content-type", "application/grpc".parse().unwrap());
+        headers.insert("date", "Sat, 17 Oct 2026 00:00:00 GMT".parse().unwrap());
+        headers.insert("server", "envoy".parse().unwrap());
+        headers.insert("x-request-id", "abc".parse().unwrap());
+        let status = Status::from_header_map(&headers).unwrap();
+
+        let prob = HttpApiProblem::from(&status);
+
+        assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+        assert_eq!(prob.detail.as_deref(), Some("no such user"));
+        assert_eq!(
+            prob.additional_fields().keys().collect::<Vec<_>>(),
+            vec!["x-request-id"]
+        );
+
+        let prob = HttpApiProblem::from_grpc_status(&status, |_| false);
+        assert!(prob.additional_fields().is_empty());
+    }
+
+    #[test]
+    fn foreign_details_are_ignored() {
+        let status = Status::with_details(
+            Code::NotFound,
+            "gone",
+            bytes::Bytes::from_static(br#"{"foo":1}"#),
+        );
+
+        let prob = HttpApiProblem::from(&status);
+
+        assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+        assert_eq!(prob.detail.as_deref(), Some("gone"));
+        assert_eq!(prob.json_value("foo"), None);
+    }
+
+    #[test]
+    fn cancelled_status_to_problem() {
+        let prob = HttpApiProblem::from(Status::cancelled(""));
+
+        assert_eq!(prob.status.map(|s| s.as_u16()), Some(499));
+        assert_eq!(prob.title.as_deref(), Some(Code::Cancelled.description()));
+        assert_eq!(prob.detail, None);
+    }
+
+    #[test]
+    fn problem_to_status_and_back() {
+        let problem = HttpApiProblem::new(StatusCode::CONFLICT)
+            .title("Taken")
+            .detail("The name is already taken")
+            .value("name", &"bob");
+
+        let status = Status::from(problem.clone());
+
+        assert_eq!(status.code(), Code::AlreadyExists);
+        assert_eq!(status.message(), "The name is already taken");
+        assert_eq!(
+            serde_json::from_slice::<HttpApiProblem>(status.details()).unwrap(),
+            problem
+        );
+        assert_eq!(HttpApiProblem::from(status), problem);
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn api_error_to_status() {
+        let error = ApiError::builder(StatusCode::SERVICE_UNAVAILABLE)
+            .message("try again later")
+            .finish();
+
+        let status = Status::from(error);
+
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(status.message(), "try again later");
+
+        let prob: HttpApiProblem = serde_json::from_slice(status.details()).unwrap();
+        assert_eq!(prob.status, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(prob.detail.as_deref(), Some("try again later"));
+    }
+}
+
+#[cfg(feature = "tower")]
+mod tower {
+    use crate::*;
+    use ::tower::{service_fn, Layer, ServiceExt};
+    use http::{Request, Response};
+    use std::convert::Infallible;
+
+    fn respond(layer: ProblemLayer, response: Response<String>) -> Response<String> {
+        respond_to(layer, Request::new(()), response)
+    }
+
+    fn respond_to(
+        layer: ProblemLayer,
+        request: Request<()>,
+        response: Response<String>,
+    ) -> Response<String> {
+        let service = layer.layer(service_fn(move |_: Request<()>| {
+            let response = response_clone(&response);
+            async move { Ok::<_, Infallible>(response) }
+        }));
+
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(service.oneshot(request))
+            .unwrap()
+    }
+
+    fn response_clone(response: &Response<String>) -> Response<String> {
+        let mut builder = Response::builder().status(response.status());
+        for (name, value) in response.headers() {
+            builder = builder.header(name, value);
+        }
+        let mut clone = builder.body(response.body().clone()).unwrap();
+        if let Some(problem) = response.extensions().get::<HttpApiProblem>() {
+            clone.extensions_mut().insert(problem.clone());
+        }
+        #[cfg(feature = "api-error")]
+        if let Some(error) = response.extensions().get::<std::sync::Arc<ApiError>>() {
+            clone.extensions_mut().insert(error.clone());
+        }
+        clone
+    }
+
+    fn problem_of(response: &Response<String>) -> HttpApiProblem {
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON_MEDIA_TYPE);
+        assert_eq!(
+            response.headers()["content-length"],
+            response.body().len().to_string().as_str()
+        );
+        serde_json::from_str(response.body()).unwrap()
+    }
+
+    #[test]
+    fn rewrites_empty_error_responses() {
+        let response = respond(
+            ProblemLayer::new(),
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(String::new())
+                .unwrap(),
+        );
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(problem_of(&response).title.as_deref(), Some("Not Found"));
+    }
+
+    #[test]
+    fn rewrites_non_problem_bodies() {
+        let response = respond(
+            ProblemLayer::new(),
+            Response::builder()
+                .status(StatusCode::BAD_GATEWAY)
+                .header("content-type", "text/html")
+                .body("<h1>Bad Gateway</h1>".to_string())
+                .unwrap(),
+        );
+
+        assert_eq!(problem_of(&response).status, Some(StatusCode::BAD_GATEWAY));
+    }
+
+    #[test]
+    fn keeps_problems_and_successes() {
+        let body = HttpApiProblem::new(StatusCode::CONFLICT)
+            .title("Taken")
+            .json_string();
+        let response = respond(
+            ProblemLayer::new(),
+            Response::builder()
+                .status(StatusCode::CONFLICT)
+                .header("content-type", PROBLEM_JSON_MEDIA_TYPE)
+                .body(body.clone())
+                .unwrap(),
+        );
+        assert_eq!(response.body(), &body);
+
+        let response = respond(ProblemLayer::new(), Response::new("fine".to_string()));
+        assert_eq!(response.body(), "fine");
+    }
+
+    #[test]
+    fn sends_only_the_headers_to_head_requests() {
+        let request = Request::builder().method("HEAD").body(()).unwrap();
+        let response = respond_to(
+            ProblemLayer::new(),
+            request,
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header("x-request-id", "abc")
+                .body(String::new())
+                .unwrap(),
+        );
+
+        let json = HttpApiProblem::with_title_and_type(StatusCode::NOT_FOUND).json_string();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON_MEDIA_TYPE);
+        assert_eq!(
+            response.headers()["content-length"],
+            json.len().to_string().as_str()
+        );
+        assert_eq!(response.headers()["x-request-id"], "abc");
+        assert_eq!(response.body(), "");
+    }
+
+    #[test]
+    fn follows_the_rules() {
+        let layer = ProblemLayer::new().rewrite_when(|status| status.is_server_error());
+
+        let response = respond(
+            layer,
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body("nothing here".to_string())
+                .unwrap(),
+        );
+
+        assert_eq!(response.body(), "nothing here");
+    }
+
+    #[test]
+    fn renders_problems_from_extensions() {
+        let mut response = Response::new(String::new());
+        response
+            .extensions_mut()
+            .insert(HttpApiProblem::new(StatusCode::CONFLICT).title("Taken"));
+
+        let response = respond(ProblemLayer::new(), response);
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(problem_of(&response).title.as_deref(), Some("Taken"));
+    }
+
+    #[test]
+    fn negotiates_the_format() {
+        let mut response = Response::new(String::new());
+        response
+            .extensions_mut()
+            .insert(HttpApiProblem::new(StatusCode::CONFLICT).title("Taken"));
+        let request = Request::builder()
+            .header("accept", "text/plain, application/json;q=0.5")
+            .body(())
+            .unwrap();
+
+        let response = respond_to(ProblemLayer::new(), request, response);
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(response.body(), "409 Conflict - Taken");
+
+        let request = Request::builder()
+            .header("accept", "application/json")
+            .body(())
+            .unwrap();
+        let response = respond_to(
+            ProblemLayer::new(),
+            request,
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(String::new())
+                .unwrap(),
+        );
+
+        assert_eq!(response.headers()["content-type"], "application/json");
+        let prob: HttpApiProblem = serde_json::from_str(response.body()).unwrap();
+        assert_eq!(prob.status, Some(StatusCode::NOT_FOUND));
+    }
+
+    #[cfg(feature = "api-error")]
+    #[test]
+    fn renders_api_errors_from_extensions() {
+        let mut response = Response::new(String::new());
+        response.extensions_mut().insert(std::sync::Arc::new(
+            ApiError::builder(StatusCode::FORBIDDEN)
+                .message("no access")
+                .finish(),
+        ));
+
+        let response = respond(ProblemLayer::new(), response);
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(problem_of(&response).detail.as_deref(), Some("no access"));
+    }
 }
@@ -0,0 +1,98 @@
+//! A [tide] middleware which responds with problems.
+//!
+//! Requires the `tide` feature
+use tide::utils::async_trait;
+use tide::{Middleware, Next, Request};
+
+use super::*;
+
+/// A [tide::Middleware] which turns error responses into `application/problem+json`.
+///
+/// If the response carries a [tide::Error] caused by an [HttpApiProblem] or an
+/// `ApiError` that one is rendered. Otherwise the problem is created from the status
+/// of the response. Responses which have a body or stream one of unknown length
+/// are only rewritten if they carry a [tide::Error].
+///
+/// The format is negotiated with the `Accept` header of the request.
+/// Only the status, body and content type of the response are replaced, so
+/// other headers like `Allow` or `WWW-Authenticate` are kept.
+/// The [tide::Error] stays attached to the response.
+///
+/// Requires the `tide` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let mut app = tide::new();
+/// app.with(TideProblemMiddleware);
+/// app.at("/").get(|_| async {
+///     Err::<String, _>(tide::Error::new(404, HttpApiProblem::new(StatusCode::NOT_FOUND)))
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TideProblemMiddleware;
+
+#[async_trait]
+impl<State: Clone + Send + Sync + 'static> Middleware<State> for TideProblemMiddleware {
+    async fn handle(&self, req: Request<State>, next: Next<'_, State>) -> tide::Result {
+        let accept = req
+            .header("Accept")
+            .map(|values| {
+                values
+                    .iter()
+                    .map(|value| value.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .unwrap_or_default();
+
+        let mut res = next.run(req).await;
+
+        let status =
+            StatusCode::from_u16(res.status().into()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        let error = res.take_error();
+
+        let problem = match error.as_ref() {
+            Some(error) => problem_from_error(error),
+            None if (status.is_client_error() || status.is_server_error())
+                && res.is_empty() == Some(true) =>
+            {
+                HttpApiProblem::with_title_and_type(status)
+            }
+            None => return Ok(res),
+        };
+
+        let format = ProblemFormat::negotiate(&accept);
+        let body = problem.format_bytes(format);
+        let length = body.len();
+
+        res.set_status(problem.status_code_or_internal_server_error());
+        res.set_body(body);
+        res.insert_header("Content-Length", length.to_string());
+        res.set_content_type(format.content_type());
+        if let Some(error) = error {
+            res.set_error(error);
+        }
+
+        Ok(res)
+    }
+}
+
+fn problem_from_error(error: &tide::Error) -> HttpApiProblem {
+    if let Some(problem) = error.downcast_ref::<HttpApiProblem>() {
+        return problem.clone();
+    }
+
+    #[cfg(feature = "api-error")]
+    if let Some(error) = error.downcast_ref::<ApiError>() {
+        return error.to_http_api_problem();
+    }
+
+    let status =
+        StatusCode::from_u16(error.status().into()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+    HttpApiProblem::with_title_and_type(status)
+}
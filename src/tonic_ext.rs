@@ -0,0 +1,239 @@
+//! Bridging between gRPC statuses of [tonic] and problems.
+//!
+//! Requires the `tonic` feature
+use tonic::metadata::KeyRef;
+use tonic::{Code, Status};
+
+use super::*;
+
+/// Returns the HTTP status for a gRPC [Code] according to the
+/// [mapping](https://cloud.google.com/apis/design/errors#handling_errors)
+/// used by gRPC-HTTP gateways.
+///
+/// `Cancelled` becomes the non-standard `499 - Client Closed Request`.
+///
+/// Requires the `tonic` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// assert_eq!(grpc_code_to_status_code(tonic::Code::NotFound), StatusCode::NOT_FOUND);
+/// assert_eq!(grpc_code_to_status_code(tonic::Code::Unavailable), StatusCode::SERVICE_UNAVAILABLE);
+/// ```
+pub fn grpc_code_to_status_code(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::Cancelled => StatusCode::from_u16(499).unwrap(),
+        Code::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
+        Code::InvalidArgument => StatusCode::BAD_REQUEST,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists => StatusCode::CONFLICT,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::FailedPrecondition => StatusCode::BAD_REQUEST,
+        Code::Aborted => StatusCode::CONFLICT,
+        Code::OutOfRange => StatusCode::BAD_REQUEST,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+    }
+}
+
+/// Returns the gRPC [Code] for an HTTP status.
+///
+/// This is the reverse of [grpc_code_to_status_code]. Where several codes
+/// map to the same status the most general one is chosen. Other client errors become
+/// `FailedPrecondition`, other server errors `Internal` and everything else `Unknown`.
+///
+/// Requires the `tonic` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// assert_eq!(status_code_to_grpc_code(StatusCode::NOT_FOUND), tonic::Code::NotFound);
+/// assert_eq!(status_code_to_grpc_code(StatusCode::IM_A_TEAPOT), tonic::Code::FailedPrecondition);
+/// ```
+pub fn status_code_to_grpc_code(status: StatusCode) -> Code {
+    match status.as_u16() {
+        200 => Code::Ok,
+        400 => Code::InvalidArgument,
+        401 => Code::Unauthenticated,
+        403 => Code::PermissionDenied,
+        404 => Code::NotFound,
+        409 => Code::AlreadyExists,
+        429 => Code::ResourceExhausted,
+        499 => Code::Cancelled,
+        501 => Code::Unimplemented,
+        503 => Code::Unavailable,
+        504 => Code::DeadlineExceeded,
+        _ if status.is_client_error() => Code::FailedPrecondition,
+        _ if status.is_server_error() => Code::Internal,
+        _ => Code::Unknown,
+    }
+}
+
+/// Returns `true` if the metadata `key` is set by the application and not
+/// by gRPC or the transport.
+///
+/// Keys starting with `grpc-` and the headers of the HTTP/2 transport like
+/// `content-type`, `date` or `server` are not application metadata.
+///
+/// Requires the `tonic` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// assert!(is_application_metadata("x-request-id"));
+/// assert!(!is_application_metadata("content-type"));
+/// assert!(!is_application_metadata("grpc-status-details-bin"));
+/// ```
+pub fn is_application_metadata(key: &str) -> bool {
+    const TRANSPORT_HEADERS: &[&str] = &[
+        "accept-encoding",
+        "alt-svc",
+        "connection",
+        "content-encoding",
+        "content-length",
+        "content-type",
+        "date",
+        "host",
+        "keep-alive",
+        "server",
+        "te",
+        "trailer",
+        "transfer-encoding",
+        "upgrade",
+        "user-agent",
+        "via",
+    ];
+
+    !key.starts_with("grpc-")
+        && !key.starts_with(':')
+        && !TRANSPORT_HEADERS
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case(key))
+}
+
+impl HttpApiProblem {
+    /// Creates a problem from a gRPC [Status] with the metadata for which
+    /// `include` returns `true` as extension members.
+    ///
+    /// See the conversion of a [Status] into an [HttpApiProblem] for the rest.
+    ///
+    /// Requires the `tonic` feature
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    /// use tonic::{metadata::MetadataValue, Code, Status};
+    ///
+    /// let mut status = Status::new(Code::NotFound, "no such user");
+    /// status.metadata_mut().insert("x-request-id", MetadataValue::from_static("abc"));
+    /// status.metadata_mut().insert("x-backend", MetadataValue::from_static("db-7"));
+    ///
+    /// let p = HttpApiProblem::from_grpc_status(&status, |key| key == "x-request-id");
+    ///
+    /// assert_eq!(Some(StatusCode::NOT_FOUND), p.status);
+    /// assert_eq!(Some("abc".to_string()), p.get_value::<&str, String>("x-request-id"));
+    /// assert_eq!(None, p.json_value("x-backend"));
+    /// ```
+    pub fn from_grpc_status<F>(status: &Status, include: F) -> Self
+    where
+        F: Fn(&str) -> bool,
+    {
+        let mut problem = match serde_json::from_slice::<HttpApiProblem>(status.details()) {
+            Ok(problem) if problem.status.is_some() => problem,
+            _ => {
+                let http_status = grpc_code_to_status_code(status.code());
+                let mut problem = HttpApiProblem::with_title_and_type(http_status);
+                if http_status.canonical_reason().is_none() {
+                    problem.title = Some(status.code().description().to_string());
+                }
+                if !status.message().is_empty() {
+                    problem.detail = Some(status.message().to_string());
+                }
+                problem
+            }
+        };
+
+        for key in status.metadata().keys() {
+            let KeyRef::Ascii(key) = key else {
+                continue;
+            };
+            if !include(key.as_str()) {
+                continue;
+            }
+
+            let mut values = status
+                .metadata()
+                .get_all(key.as_str())
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .collect::<Vec<_>>();
+
+            match values.len() {
+                0 => {}
+                1 => problem.set_value(key.as_str(), &values.remove(0)),
+                _ => problem.set_value(key.as_str(), &values),
+            }
+        }
+
+        problem
+    }
+}
+
+impl From<&Status> for HttpApiProblem {
+    /// Creates a problem from a gRPC [Status].
+    ///
+    /// If the details of the [Status] contain a problem JSON with a `status` as created by
+    /// the conversion of an [HttpApiProblem] into a [Status] that problem is restored.
+    /// Otherwise the status is mapped with [grpc_code_to_status_code] and the message
+    /// of the [Status] becomes the `detail`.
+    ///
+    /// ASCII metadata for which [is_application_metadata] returns `true` become
+    /// extension members. Keys with multiple values become arrays.
+    /// Use [HttpApiProblem::from_grpc_status] to select the metadata yourself.
+    fn from(status: &Status) -> Self {
+        HttpApiProblem::from_grpc_status(status, is_application_metadata)
+    }
+}
+
+impl From<Status> for HttpApiProblem {
+    fn from(status: Status) -> Self {
+        HttpApiProblem::from(&status)
+    }
+}
+
+impl From<HttpApiProblem> for Status {
+    /// Creates a gRPC [Status] with the problem JSON as its details.
+    ///
+    /// The code is mapped with [status_code_to_grpc_code]. The `detail`
+    /// or, if there is none, the `title` becomes the message.
+    fn from(problem: HttpApiProblem) -> Self {
+        let code = status_code_to_grpc_code(problem.status_or_internal_server_error());
+        let details = bytes::Bytes::from(problem.json_bytes());
+        let message = problem.detail.or(problem.title).unwrap_or_default();
+
+        Status::with_details(code, message, details)
+    }
+}
+
+#[cfg(feature = "api-error")]
+impl From<ApiError> for Status {
+    /// Creates a gRPC [Status] with the problem JSON of the error as its details.
+    ///
+    /// See the conversion of [HttpApiProblem] into [Status].
+    fn from(error: ApiError) -> Self {
+        error.into_http_api_problem().into()
+    }
+}
@@ -0,0 +1,214 @@
+//! A [tower] layer which turns error responses into problems.
+//!
+//! Requires the `tower` feature
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http::header::{HeaderValue, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Method, Request, Response};
+use tower::{Layer, Service};
+
+use super::*;
+
+type Rule = Arc<dyn Fn(StatusCode) -> bool + Send + Sync>;
+
+/// A [tower::Layer] which rewrites error responses into `application/problem+json`.
+///
+/// A response is rewritten if
+///
+/// * it carries an [HttpApiProblem] or an `Arc<ApiError>` in its extensions.
+///   That one is rendered then.
+/// * its status matches the rules of the layer and it does not already
+///   contain an `application/problem+json`. The problem is created
+///   from the status then.
+///
+/// By default all client and server errors are rewritten.
+///
+/// The format of the problem is negotiated with the `Accept` header
+/// of the request. See [ProblemFormat::negotiate].
+///
+/// The response to a `HEAD` request gets the headers of the problem but an empty body.
+///
+/// The body of the response must be constructible from a [String]. This is the case
+/// for `String`, `Vec<u8>`, `http_body_util::Full<Bytes>` and the body of axum.
+///
+/// Requires the `tower` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// // Leave `404 - Not Found` as it is
+/// let layer = ProblemLayer::new().rewrite_when(|status| {
+///     status != StatusCode::NOT_FOUND && (status.is_client_error() || status.is_server_error())
+/// });
+/// ```
+#[derive(Clone)]
+pub struct ProblemLayer {
+    rule: Rule,
+}
+
+impl ProblemLayer {
+    /// Creates a layer which rewrites all client and server errors
+    pub fn new() -> Self {
+        ProblemLayer {
+            rule: Arc::new(|status| status.is_client_error() || status.is_server_error()),
+        }
+    }
+
+    /// Sets the rule which decides whether a response with
+    /// the given [StatusCode] gets rewritten.
+    ///
+    /// Responses carrying a problem in their extensions are always rewritten.
+    pub fn rewrite_when<F>(mut self, rule: F) -> Self
+    where
+        F: Fn(StatusCode) -> bool + Send + Sync + 'static,
+    {
+        self.rule = Arc::new(rule);
+        self
+    }
+}
+
+impl Default for ProblemLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ProblemLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProblemLayer").finish_non_exhaustive()
+    }
+}
+
+impl<S> Layer<S> for ProblemLayer {
+    type Service = ProblemService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ProblemService {
+            inner,
+            rule: self.rule.clone(),
+        }
+    }
+}
+
+/// The [tower::Service] created by a [ProblemLayer]
+///
+/// Requires the `tower` feature
+#[derive(Clone)]
+pub struct ProblemService<S> {
+    inner: S,
+    rule: Rule,
+}
+
+impl<S: fmt::Debug> fmt::Debug for ProblemService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProblemService")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, ReqB, B> Service<Request<ReqB>> for ProblemService<S>
+where
+    S: Service<Request<ReqB>, Response = Response<B>>,
+    B: From<String>,
+{
+    type Response = Response<B>;
+    type Error = S::Error;
+    type Future = ProblemFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqB>) -> Self::Future {
+        let format = ProblemFormat::from_headers(req.headers());
+        let head = req.method() == Method::HEAD;
+
+        ProblemFuture {
+            inner: self.inner.call(req),
+            rule: self.rule.clone(),
+            format,
+            head,
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// The response future of a [ProblemService]
+    ///
+    /// Requires the `tower` feature
+    pub struct ProblemFuture<F> {
+        #[pin]
+        inner: F,
+        rule: Rule,
+        format: ProblemFormat,
+        head: bool,
+    }
+}
+
+impl<F, B, E> Future for ProblemFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+    B: From<String>,
+{
+    type Output = Result<Response<B>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        match this.inner.poll(cx) {
+            Poll::Ready(Ok(response)) => {
+                Poll::Ready(Ok(rewrite(this.rule, *this.format, *this.head, response)))
+            }
+            other => other,
+        }
+    }
+}
+
+fn rewrite<B: From<String>>(
+    rule: &Rule,
+    format: ProblemFormat,
+    head: bool,
+    mut response: Response<B>,
+) -> Response<B> {
+    let problem = response.extensions_mut().remove::<HttpApiProblem>();
+
+    #[cfg(feature = "api-error")]
+    let problem = problem.or_else(|| {
+        response
+            .extensions_mut()
+            .remove::<Arc<ApiError>>()
+            .map(|error| error.to_http_api_problem())
+    });
+
+    let problem = match problem {
+        Some(problem) => problem,
+        None if rule(response.status()) && !is_problem_json_response(response.headers()) => {
+            HttpApiProblem::with_title_and_type(response.status())
+        }
+        None => return response,
+    };
+
+    let body = problem.format_string(format);
+    let (mut parts, _) = response.into_parts();
+
+    parts.status = problem.status_or_internal_server_error();
+    parts.headers.remove(CONTENT_ENCODING);
+    parts.headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    parts
+        .headers
+        .insert(CONTENT_LENGTH, HeaderValue::from(body.len()));
+
+    if head {
+        Response::from_parts(parts, B::from(String::new()))
+    } else {
+        Response::from_parts(parts, B::from(body))
+    }
+}
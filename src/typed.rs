@@ -0,0 +1,206 @@
+//! Problems with typed extension members
+use std::convert::TryFrom;
+
+use serde::ser::Error as SerError;
+
+use super::*;
+
+/// An [HttpApiProblem] whose extension members are given by a type `E`.
+///
+/// The members of `E` are flattened into the problem. This allows
+/// producers and consumers to share a type for the extension members
+/// instead of accessing untyped values via [HttpApiProblem::get_value].
+///
+/// `E` must serialize to an object (or `null` for no extension members).
+/// The names of its members must not be members of the problem itself.
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct OutOfCredit {
+///     balance: u32,
+///     accounts: Vec<String>,
+/// }
+///
+/// let p = TypedProblem::new(
+///     StatusCode::FORBIDDEN,
+///     OutOfCredit {
+///         balance: 30,
+///         accounts: vec!["/account/12345".to_string()],
+///     },
+/// )
+/// .title("You do not have enough credit.");
+///
+/// let json = serde_json::to_value(&p).unwrap();
+///
+/// assert_eq!(json["title"], "You do not have enough credit.");
+/// assert_eq!(json["balance"], 30);
+///
+/// let parsed: TypedProblem<OutOfCredit> = serde_json::from_value(json).unwrap();
+///
+/// assert_eq!(parsed.extensions.balance, 30);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedProblem<E> {
+    /// A URI reference that identifies the problem type.
+    pub type_url: Option<String>,
+
+    /// The HTTP status code generated by the origin server.
+    pub status: Option<StatusCode>,
+
+    /// A short, human-readable summary of the problem type.
+    pub title: Option<String>,
+
+    /// A human-readable explanation specific to this
+    /// occurrence of the problem.
+    pub detail: Option<String>,
+
+    /// A URI reference that identifies the specific
+    /// occurrence of the problem.
+    pub instance: Option<String>,
+
+    /// The extension members
+    pub extensions: E,
+}
+
+impl<E> TypedProblem<E> {
+    /// Creates a new instance with the given [StatusCode] and extension members.
+    pub fn new<T: Into<StatusCode>>(status: T, extensions: E) -> Self {
+        TypedProblem {
+            type_url: None,
+            status: Some(status.into()),
+            title: None,
+            detail: None,
+            instance: None,
+            extensions,
+        }
+    }
+
+    /// Sets the `type_url`
+    pub fn type_url<T: Into<String>>(mut self, type_url: T) -> Self {
+        self.type_url = Some(type_url.into());
+        self
+    }
+
+    /// Sets the `title`
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the `detail`
+    pub fn detail<T: Into<String>>(mut self, detail: T) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the `instance`
+    pub fn instance<T: Into<String>>(mut self, instance: T) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+}
+
+impl<E: Serialize> TypedProblem<E> {
+    /// Turns this into an untyped [HttpApiProblem].
+    ///
+    /// Fails if the extension members do not serialize to an object or
+    /// if one of the members is named like a member of the problem itself.
+    pub fn to_http_api_problem(&self) -> Result<HttpApiProblem, serde_json::Error> {
+        let mut problem = HttpApiProblem {
+            type_url: self.type_url.clone(),
+            status: self.status,
+            title: self.title.clone(),
+            detail: self.detail.clone(),
+            instance: self.instance.clone(),
+            additional_fields: HashMap::new(),
+            raw_status: None,
+        };
+
+        match serde_json::to_value(&self.extensions)? {
+            Value::Null => {}
+            Value::Object(members) => {
+                for (key, value) in members {
+                    problem
+                        .try_set_value(key, &value)
+                        .map_err(<serde_json::Error as SerError>::custom)?;
+                }
+            }
+            _ => {
+                return Err(<serde_json::Error as SerError>::custom(
+                    "the extension members must serialize to an object",
+                ))
+            }
+        }
+
+        Ok(problem)
+    }
+}
+
+impl<E: Serialize> TryFrom<TypedProblem<E>> for HttpApiProblem {
+    type Error = serde_json::Error;
+
+    fn try_from(problem: TypedProblem<E>) -> Result<Self, Self::Error> {
+        problem.to_http_api_problem()
+    }
+}
+
+impl<E: DeserializeOwned> TryFrom<HttpApiProblem> for TypedProblem<E> {
+    type Error = serde_json::Error;
+
+    /// Fails if the extension members of the [HttpApiProblem]
+    /// can not be deserialized into `E`
+    fn try_from(problem: HttpApiProblem) -> Result<Self, Self::Error> {
+        let no_members = problem.additional_fields.is_empty();
+        let members = problem.additional_fields.into_iter().collect();
+        let extensions = match serde_json::from_value(Value::Object(members)) {
+            // `E` might be a type like `()` which serializes to `null`
+            Err(_) if no_members => serde_json::from_value(Value::Null)?,
+            result => result?,
+        };
+
+        Ok(TypedProblem {
+            type_url: problem.type_url,
+            status: problem.status,
+            title: problem.title,
+            detail: problem.detail,
+            instance: problem.instance,
+            extensions,
+        })
+    }
+}
+
+impl HttpApiProblem {
+    /// Converts this into a [TypedProblem] with the extension members deserialized into `E`.
+    ///
+    /// Fails if the extension members can not be deserialized into `E`
+    pub fn into_typed<E: DeserializeOwned>(self) -> Result<TypedProblem<E>, serde_json::Error> {
+        TypedProblem::try_from(self)
+    }
+}
+
+impl<E: Serialize> Serialize for TypedProblem<E> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_http_api_problem()
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl<'de, E: DeserializeOwned> Deserialize<'de> for TypedProblem<E> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        HttpApiProblem::deserialize(deserializer)?
+            .into_typed()
+            .map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,700 @@
+//! Problems describing invalid request parameters
+//!
+//! Both RFCs show how to describe invalid request parameters in their examples.
+//! [RFC7807](https://tools.ietf.org/html/rfc7807#section-3) uses an
+//! `invalid-params` member:
+//!
+//! ```javascript
+//! {
+//!    "type": "https://example.net/validation-error",
+//!    "title": "Your request parameters didn't validate.",
+//!    "invalid-params": [ {
+//!                          "name": "age",
+//!                          "reason": "must be a positive integer"
+//!                        }
+//!                      ]
+//! }
+//! ```
+//!
+//! while [RFC9457](https://www.rfc-editor.org/rfc/rfc9457#section-3) uses
+//! an `errors` member since `invalid-params` is no longer a recommended member name:
+//!
+//! ```javascript
+//! {
+//!    "type": "https://example.net/validation-error",
+//!    "title": "Your request is not valid.",
+//!    "errors": [ {
+//!                  "detail": "must be a positive integer",
+//!                  "pointer": "#/age"
+//!                }
+//!              ]
+//! }
+//! ```
+//!
+//! [InvalidParam]s are rendered in the shape of the [RfcMode] in effect.
+use serde::ser::SerializeMap;
+use serde_json::Map;
+
+use super::*;
+
+/// The name of the member containing the [InvalidParam]s with [RfcMode::Rfc7807]
+///
+/// "invalid-params"
+pub static INVALID_PARAMS_MEMBER: &str = "invalid-params";
+
+/// The name of the member containing the [InvalidParam]s with [RfcMode::Rfc9457]
+///
+/// "errors"
+pub static ERRORS_MEMBER: &str = "errors";
+
+/// Returns the name of the member containing the [InvalidParam]s
+/// for the [RfcMode] in effect.
+pub fn invalid_params_member() -> &'static str {
+    match rfc_mode() {
+        RfcMode::Rfc7807 => INVALID_PARAMS_MEMBER,
+        RfcMode::Rfc9457 => ERRORS_MEMBER,
+    }
+}
+
+/// Where an invalid value was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamLocation {
+    /// A [JSON pointer](https://tools.ietf.org/html/rfc6901) into the request body
+    /// like `/age`
+    ///
+    /// With [RfcMode::Rfc9457] it is serialized as a URI fragment like `#/age`.
+    Pointer(String),
+    /// The name of a parameter, e.g. of the query string
+    Parameter(String),
+}
+
+impl ParamLocation {
+    /// The JSON pointer or the name of the parameter
+    pub fn as_str(&self) -> &str {
+        match self {
+            ParamLocation::Pointer(s) | ParamLocation::Parameter(s) => s,
+        }
+    }
+}
+
+/// Describes a single invalid value of a request
+///
+/// Serialized as `{"name": ..., "reason": ...}` with [RfcMode::Rfc7807] and
+/// as `{"pointer": ..., "detail": ...}` or `{"parameter": ..., "detail": ...}`
+/// with [RfcMode::Rfc9457]. An optional `code` and additional values are added
+/// as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidParam {
+    /// Where the invalid value was found
+    pub location: ParamLocation,
+    /// Why the value is invalid
+    pub reason: String,
+    /// A machine readable code of the error
+    pub code: Option<String>,
+    /// Additional values describing the error
+    pub values: Map<String, Value>,
+}
+
+impl InvalidParam {
+    /// Creates an [InvalidParam] for a value in the body given by a JSON pointer
+    pub fn pointer<P: Into<String>, R: Into<String>>(pointer: P, reason: R) -> Self {
+        Self::new(ParamLocation::Pointer(pointer.into()), reason)
+    }
+
+    /// Creates an [InvalidParam] for a named parameter
+    pub fn parameter<N: Into<String>, R: Into<String>>(name: N, reason: R) -> Self {
+        Self::new(ParamLocation::Parameter(name.into()), reason)
+    }
+
+    /// Creates an [InvalidParam] for the given [ParamLocation]
+    pub fn new<R: Into<String>>(location: ParamLocation, reason: R) -> Self {
+        InvalidParam {
+            location,
+            reason: reason.into(),
+            code: None,
+            values: Map::new(),
+        }
+    }
+
+    /// Sets a machine readable code of the error
+    pub fn code<C: Into<String>>(mut self, code: C) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Adds a value describing the error.
+    ///
+    /// If the value is not serializable nothing happens.
+    pub fn value<K: Into<String>, V: Serialize>(mut self, key: K, value: &V) -> Self {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.values.insert(key.into(), value);
+        }
+        self
+    }
+}
+
+impl Serialize for InvalidParam {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+
+        match (rfc_mode(), &self.location) {
+            (RfcMode::Rfc7807, location) => {
+                map.serialize_entry("name", location.as_str())?;
+                map.serialize_entry("reason", &self.reason)?;
+            }
+            (RfcMode::Rfc9457, ParamLocation::Pointer(pointer)) => {
+                map.serialize_entry("detail", &self.reason)?;
+                map.serialize_entry("pointer", &pointer_to_fragment(pointer))?;
+            }
+            (RfcMode::Rfc9457, ParamLocation::Parameter(name)) => {
+                map.serialize_entry("detail", &self.reason)?;
+                map.serialize_entry("parameter", name)?;
+            }
+        }
+        if let Some(code) = self.code.as_ref() {
+            map.serialize_entry("code", code)?;
+        }
+        for (key, value) in self.values.iter() {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for InvalidParam {
+    /// Accepts the shapes of both [RfcMode]s
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut members = Map::<String, Value>::deserialize(deserializer)?;
+
+        let mut take = |name: &str| match members.remove(name) {
+            Some(Value::String(s)) => Some(s),
+            Some(other) => {
+                members.insert(name.to_string(), other);
+                None
+            }
+            None => None,
+        };
+
+        let location = if let Some(pointer) = take("pointer") {
+            ParamLocation::Pointer(fragment_to_pointer(&pointer))
+        } else if let Some(name) = take("parameter") {
+            ParamLocation::Parameter(name)
+        } else if let Some(name) = take("name") {
+            if name.starts_with('/') || name.starts_with('#') {
+                ParamLocation::Pointer(fragment_to_pointer(&name))
+            } else {
+                ParamLocation::Parameter(name)
+            }
+        } else {
+            return Err(D::Error::missing_field("name"));
+        };
+
+        let reason = take("reason")
+            .or_else(|| take("detail"))
+            .ok_or_else(|| D::Error::missing_field("reason"))?;
+        let code = take("code");
+
+        Ok(InvalidParam {
+            location,
+            reason,
+            code,
+            values: members,
+        })
+    }
+}
+
+/// A builder for problems describing invalid request parameters
+///
+/// The default status is `400 - Bad Request`.
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let p: HttpApiProblem = ValidationProblem::new()
+///     .title("Your request parameters didn't validate.")
+///     .parameter("age", "must be a positive integer")
+///     .param(InvalidParam::pointer("/color", "must be 'green', 'red' or 'blue'").code("enum"))
+///     .into();
+///
+/// assert_eq!(Some(StatusCode::BAD_REQUEST), p.status);
+///
+/// let json = serde_json::to_value(&p).unwrap();
+///
+/// assert_eq!(json["invalid-params"][0]["name"], "age");
+/// assert_eq!(json["invalid-params"][1]["code"], "enum");
+///
+/// let params = p.get_invalid_params().unwrap();
+///
+/// assert_eq!(params[0], InvalidParam::parameter("age", "must be a positive integer"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationProblem {
+    status: StatusCode,
+    title: Option<String>,
+    detail: Option<String>,
+    type_url: Option<String>,
+    instance: Option<String>,
+    params: Vec<InvalidParam>,
+}
+
+impl ValidationProblem {
+    /// Creates a new instance with the status `400 - Bad Request`
+    pub fn new() -> Self {
+        Self::with_status(StatusCode::BAD_REQUEST)
+    }
+
+    /// Creates a new instance with the given status
+    pub fn with_status<T: Into<StatusCode>>(status: T) -> Self {
+        ValidationProblem {
+            status: status.into(),
+            title: None,
+            detail: None,
+            type_url: None,
+            instance: None,
+            params: Vec::new(),
+        }
+    }
+
+    /// Sets the [StatusCode]
+    pub fn status<T: Into<StatusCode>>(mut self, status: T) -> Self {
+        self.status = status.into();
+        self
+    }
+
+    /// Sets the `title`
+    ///
+    /// If not set the `title` is derived from the status.
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the `detail`
+    pub fn detail<T: Into<String>>(mut self, detail: T) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the `type_url`
+    pub fn type_url<T: Into<String>>(mut self, type_url: T) -> Self {
+        self.type_url = Some(type_url.into());
+        self
+    }
+
+    /// Sets the `instance`
+    pub fn instance<T: Into<String>>(mut self, instance: T) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Adds an [InvalidParam]
+    pub fn param(mut self, param: InvalidParam) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    /// Adds an [InvalidParam] for a value in the body given by a JSON pointer
+    pub fn pointer<P: Into<String>, R: Into<String>>(self, pointer: P, reason: R) -> Self {
+        self.param(InvalidParam::pointer(pointer, reason))
+    }
+
+    /// Adds an [InvalidParam] for a named parameter
+    pub fn parameter<N: Into<String>, R: Into<String>>(self, name: N, reason: R) -> Self {
+        self.param(InvalidParam::parameter(name, reason))
+    }
+
+    /// Adds an [InvalidParam]
+    pub fn push(&mut self, param: InvalidParam) {
+        self.params.push(param);
+    }
+
+    /// Returns the collected [InvalidParam]s
+    pub fn params(&self) -> &[InvalidParam] {
+        &self.params
+    }
+
+    /// Returns `true` if no [InvalidParam]s have been collected
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Turns this into an [HttpApiProblem]
+    pub fn into_http_api_problem(self) -> HttpApiProblem {
+        let mut problem = HttpApiProblem::with_title(self.status);
+
+        if let Some(title) = self.title {
+            problem.title = Some(title);
+        }
+        problem.detail = self.detail;
+        problem.type_url = self.type_url;
+        problem.instance = self.instance;
+
+        problem.invalid_params(self.params)
+    }
+}
+
+impl Default for ValidationProblem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<ValidationProblem> for HttpApiProblem {
+    fn from(problem: ValidationProblem) -> Self {
+        problem.into_http_api_problem()
+    }
+}
+
+#[cfg(feature = "api-error")]
+impl IntoApiError for ValidationProblem {
+    fn into_api_error(self) -> ApiError {
+        let mut builder = ApiError::builder(self.status).invalid_params(self.params);
+        builder.title = self.title;
+        builder.message = self.detail;
+        builder.type_url = self.type_url;
+        builder.instance = self.instance;
+        builder.finish()
+    }
+}
+
+impl HttpApiProblem {
+    /// Sets the [InvalidParam]s
+    ///
+    /// The name of the member depends on the [RfcMode] in effect.
+    /// See [invalid_params_member].
+    pub fn invalid_params<I>(mut self, params: I) -> Self
+    where
+        I: IntoIterator<Item = InvalidParam>,
+    {
+        let params: Vec<_> = params.into_iter().collect();
+        self.set_value(invalid_params_member(), &params);
+        self
+    }
+
+    /// Returns the [InvalidParam]s contained in an `invalid-params` or `errors` member
+    ///
+    /// Returns `None` if there is no such member or it can not be deserialized.
+    pub fn get_invalid_params(&self) -> Option<Vec<InvalidParam>> {
+        self.get_value::<&str, _>(INVALID_PARAMS_MEMBER)
+            .or_else(|| self.get_value::<&str, _>(ERRORS_MEMBER))
+    }
+}
+
+#[cfg(feature = "api-error")]
+impl ApiErrorBuilder {
+    /// Sets the [InvalidParam]s
+    ///
+    /// The name of the field depends on the [RfcMode] in effect.
+    /// See [invalid_params_member].
+    pub fn invalid_params<I>(self, params: I) -> Self
+    where
+        I: IntoIterator<Item = InvalidParam>,
+    {
+        let params: Vec<_> = params.into_iter().collect();
+        self.field(invalid_params_member(), params)
+    }
+}
+
+/// Turns a JSON pointer into its URI fragment representation according to
+/// [RFC6901](https://tools.ietf.org/html/rfc6901#section-6), e.g. `/a b` into `#/a%20b`
+fn pointer_to_fragment(pointer: &str) -> String {
+    let mut fragment = String::with_capacity(pointer.len() + 1);
+    fragment.push('#');
+    for byte in pointer.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'!'
+            | b'$'
+            | b'&'
+            | b'\''
+            | b'('
+            | b')'
+            | b'*'
+            | b'+'
+            | b','
+            | b';'
+            | b'='
+            | b':'
+            | b'@'
+            | b'/'
+            | b'?' => fragment.push(byte as char),
+            other => fragment.push_str(&format!("%{:02X}", other)),
+        }
+    }
+    fragment
+}
+
+/// Turns a JSON pointer in its URI fragment representation back into a JSON pointer.
+///
+/// Pointers which are not URI fragments are returned as they are.
+fn fragment_to_pointer(fragment: &str) -> String {
+    let Some(encoded) = fragment.strip_prefix('#') else {
+        return fragment.to_string();
+    };
+
+    let encoded = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut idx = 0;
+    while idx < encoded.len() {
+        let byte = encoded.get(idx + 1..idx + 3).and_then(|hex| {
+            std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        });
+        match (encoded[idx], byte) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            (other, _) => {
+                decoded.push(other);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Escapes a segment of a JSON pointer according to
+/// [RFC6901](https://tools.ietf.org/html/rfc6901#section-3)
+#[cfg(any(feature = "validator", feature = "garde"))]
+fn escape_pointer_segment(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/// Creates a `422 - Unprocessable Entity` with an [InvalidParam] for each error.
+///
+/// Nested errors are flattened with a JSON pointer as the location,
+/// e.g. `/address/street` or `/items/0/name`. Each [InvalidParam] contains
+/// the code and the params of the error. The message of the error becomes
+/// the reason. If there is no message the code is the reason.
+///
+/// Requires the `validator` feature
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for ValidationProblem {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        fn collect(
+            errors: &validator::ValidationErrors,
+            pointer: &str,
+            into: &mut Vec<InvalidParam>,
+        ) {
+            use validator::ValidationErrorsKind;
+
+            for (field, kind) in errors.errors() {
+                // errors of the whole struct are keyed with `__all__`
+                let pointer = if field == "__all__" {
+                    pointer.to_string()
+                } else {
+                    format!("{}/{}", pointer, escape_pointer_segment(field))
+                };
+
+                match kind {
+                    ValidationErrorsKind::Field(errors) => {
+                        for error in errors {
+                            let reason = error.message.as_deref().unwrap_or(&error.code);
+                            let mut param = InvalidParam::pointer(pointer.clone(), reason)
+                                .code(error.code.as_ref());
+                            if !error.params.is_empty() {
+                                param = param.value("params", &error.params);
+                            }
+                            into.push(param);
+                        }
+                    }
+                    ValidationErrorsKind::Struct(errors) => collect(errors, &pointer, into),
+                    ValidationErrorsKind::List(items) => {
+                        for (idx, errors) in items {
+                            collect(errors, &format!("{}/{}", pointer, idx), into);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut params = Vec::new();
+        collect(&errors, "", &mut params);
+        // The errors are collected from a `HashMap`
+        params.sort_by(|a, b| a.location.as_str().cmp(b.location.as_str()));
+
+        let mut problem = ValidationProblem::with_status(StatusCode::UNPROCESSABLE_ENTITY);
+        problem.params = params;
+        problem
+    }
+}
+
+/// See `From<validator::ValidationErrors> for ValidationProblem`
+///
+/// Requires the `validator` feature
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for HttpApiProblem {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        ValidationProblem::from(errors).into_http_api_problem()
+    }
+}
+
+/// See `From<validator::ValidationErrors> for ValidationProblem`
+///
+/// Requires the features `validator` and `api-error`
+#[cfg(all(feature = "validator", feature = "api-error"))]
+impl IntoApiError for validator::ValidationErrors {
+    fn into_api_error(self) -> ApiError {
+        ValidationProblem::from(self).into_api_error()
+    }
+}
+
+/// Configures how a [garde::Report] is converted into a [ValidationProblem]
+///
+/// By default the status is `422 - Unprocessable Entity` and the path of each
+/// error becomes a JSON pointer, e.g. `/items/1/amount`.
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+///
+/// let mut report = garde::Report::new();
+/// report.append(
+///     garde::Path::new("items").join(1).join("amount"),
+///     garde::Error::new("lower than 1"),
+/// );
+///
+/// let p: HttpApiProblem = GardeMapping::new().convert(&report).into();
+///
+/// assert_eq!(Some(StatusCode::UNPROCESSABLE_ENTITY), p.status);
+/// assert_eq!(
+///     Some(vec![InvalidParam::pointer("/items/1/amount", "lower than 1")]),
+///     p.get_invalid_params()
+/// );
+///
+/// let p: HttpApiProblem = GardeMapping::new()
+///     .status(StatusCode::BAD_REQUEST)
+///     .parameters()
+///     .convert(&report)
+///     .into();
+///
+/// assert_eq!(Some(StatusCode::BAD_REQUEST), p.status);
+/// assert_eq!(
+///     Some(vec![InvalidParam::parameter("items[1].amount", "lower than 1")]),
+///     p.get_invalid_params()
+/// );
+/// ```
+///
+/// Requires the `garde` feature
+#[cfg(feature = "garde")]
+#[derive(Debug, Clone)]
+pub struct GardeMapping {
+    status: StatusCode,
+    parameters: bool,
+}
+
+#[cfg(feature = "garde")]
+impl GardeMapping {
+    /// Creates the default mapping
+    pub fn new() -> Self {
+        GardeMapping {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            parameters: false,
+        }
+    }
+
+    /// Sets the [StatusCode] of the [ValidationProblem]
+    pub fn status<T: Into<StatusCode>>(mut self, status: T) -> Self {
+        self.status = status.into();
+        self
+    }
+
+    /// Use the paths as formatted by `garde` (e.g. `items[1].amount`) as the names
+    /// of parameters instead of JSON pointers.
+    ///
+    /// This is useful if the validated values were not part of the request body.
+    pub fn parameters(mut self) -> Self {
+        self.parameters = true;
+        self
+    }
+
+    /// Converts the [garde::Report] into a [ValidationProblem]
+    pub fn convert(&self, report: &garde::Report) -> ValidationProblem {
+        let mut problem = ValidationProblem::with_status(self.status);
+
+        for (path, error) in report.iter() {
+            let location = if self.parameters {
+                ParamLocation::Parameter(path.to_string())
+            } else {
+                ParamLocation::Pointer(garde_path_to_pointer(path))
+            };
+
+            problem.push(InvalidParam::new(location, error.message()));
+        }
+
+        problem
+    }
+}
+
+/// Turns the components of a [garde::Path] into a JSON pointer
+///
+/// The formatted path (e.g. `items[1].amount`) can not be used since keys
+/// may contain `.` or `[`. `garde` does not expose the components other than
+/// via `__iter`, which is why the `garde` dependency is limited to `0.22`.
+#[cfg(feature = "garde")]
+fn garde_path_to_pointer(path: &garde::Path) -> String {
+    path.__iter()
+        .rev()
+        .filter(|(kind, _)| *kind != garde::error::Kind::None)
+        .fold(String::new(), |mut pointer, (_, component)| {
+            pointer.push('/');
+            pointer.push_str(&escape_pointer_segment(component));
+            pointer
+        })
+}
+
+#[cfg(feature = "garde")]
+impl Default for GardeMapping {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Converts the [garde::Report] with the default [GardeMapping]
+///
+/// Requires the `garde` feature
+#[cfg(feature = "garde")]
+impl From<garde::Report> for ValidationProblem {
+    fn from(report: garde::Report) -> Self {
+        GardeMapping::new().convert(&report)
+    }
+}
+
+/// Converts the [garde::Report] with the default [GardeMapping]
+///
+/// Requires the `garde` feature
+#[cfg(feature = "garde")]
+impl From<garde::Report> for HttpApiProblem {
+    fn from(report: garde::Report) -> Self {
+        ValidationProblem::from(report).into_http_api_problem()
+    }
+}
+
+/// Converts the [garde::Report] with the default [GardeMapping]
+///
+/// Requires the features `garde` and `api-error`
+#[cfg(all(feature = "garde", feature = "api-error"))]
+impl IntoApiError for garde::Report {
+    fn into_api_error(self) -> ApiError {
+        ValidationProblem::from(self).into_api_error()
+    }
+}
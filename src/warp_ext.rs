@@ -0,0 +1,170 @@
+//! Turning [warp] rejections into problem responses.
+//!
+//! Requires the `warp` feature
+use std::convert::Infallible;
+
+use warp::body::BodyDeserializeError;
+use warp::reject::{
+    InvalidHeader, InvalidQuery, LengthRequired, MethodNotAllowed, MissingCookie, MissingHeader,
+    PayloadTooLarge, UnsupportedMediaType,
+};
+use warp::{Filter, Rejection, Reply};
+
+use super::*;
+
+impl HttpApiProblem {
+    /// Creates an [HttpApiProblem] from a [warp::Rejection].
+    ///
+    /// If the rejection was caused by an [HttpApiProblem] or an `ApiError`
+    /// that one is used. The built-in rejections of warp are
+    /// mapped to problems with a matching status. Any other rejection
+    /// becomes a `500 - Internal Server Error`.
+    ///
+    /// Requires the `warp` feature
+    pub fn from_warp_rejection(rejection: &Rejection) -> Self {
+        if let Some(problem) = rejection.find::<HttpApiProblem>() {
+            return problem.clone();
+        }
+
+        #[cfg(feature = "api-error")]
+        if let Some(error) = rejection.find::<ApiError>() {
+            return error.to_http_api_problem();
+        }
+
+        if rejection.is_not_found() {
+            return Self::with_title_and_type(StatusCode::NOT_FOUND);
+        }
+
+        if let Some(err) = rejection.find::<MethodNotAllowed>() {
+            return Self::with_title_and_type(StatusCode::METHOD_NOT_ALLOWED)
+                .detail(err.to_string());
+        }
+
+        if let Some(err) = rejection.find::<InvalidHeader>() {
+            return Self::with_title_and_type(StatusCode::BAD_REQUEST).detail(err.to_string());
+        }
+
+        if let Some(err) = rejection.find::<MissingHeader>() {
+            return Self::with_title_and_type(StatusCode::BAD_REQUEST).detail(err.to_string());
+        }
+
+        if let Some(err) = rejection.find::<MissingCookie>() {
+            return Self::with_title_and_type(StatusCode::BAD_REQUEST).detail(err.to_string());
+        }
+
+        if let Some(err) = rejection.find::<InvalidQuery>() {
+            return Self::with_title_and_type(StatusCode::BAD_REQUEST).detail(err.to_string());
+        }
+
+        if let Some(err) = rejection.find::<BodyDeserializeError>() {
+            return Self::with_title_and_type(StatusCode::BAD_REQUEST).detail(err.to_string());
+        }
+
+        if let Some(err) = rejection.find::<LengthRequired>() {
+            return Self::with_title_and_type(StatusCode::LENGTH_REQUIRED).detail(err.to_string());
+        }
+
+        if let Some(err) = rejection.find::<PayloadTooLarge>() {
+            return Self::with_title_and_type(StatusCode::PAYLOAD_TOO_LARGE)
+                .detail(err.to_string());
+        }
+
+        if let Some(err) = rejection.find::<UnsupportedMediaType>() {
+            return Self::with_title_and_type(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+                .detail(err.to_string());
+        }
+
+        Self::with_title_and_type(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+/// Renders a [warp::Rejection] as an `application/problem+json` response.
+///
+/// To be used with [warp::Filter::recover]. See [HttpApiProblem::from_warp_rejection]
+/// for how rejections are turned into problems.
+/// Use [negotiate_warp_rejections] to respond in the format the client accepts.
+///
+/// Requires the `warp` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+/// use warp::Filter;
+///
+/// let routes = warp::path("hello")
+///     .and_then(|| async {
+///         Err::<String, _>(warp::reject::custom(HttpApiProblem::new(StatusCode::IM_A_TEAPOT)))
+///     })
+///     .recover(recover_warp_rejection);
+/// ```
+pub async fn recover_warp_rejection(
+    rejection: Rejection,
+) -> Result<warp::reply::Response, Infallible> {
+    Ok(HttpApiProblem::from_warp_rejection(&rejection).to_warp_response())
+}
+
+/// Renders the rejections of `filter` as problems in the format negotiated
+/// with the `Accept` header of the request.
+///
+/// This replaces [warp::Filter::recover] with [recover_warp_rejection], which can
+/// not access the request. See [HttpApiProblem::from_warp_rejection] for how
+/// rejections are turned into problems and [ProblemFormat::negotiate] for the formats.
+///
+/// Requires the `warp` feature
+///
+/// # Example
+///
+/// ```rust
+/// use http_api_problem::*;
+/// use warp::Filter;
+///
+/// let hello = warp::path("hello").and_then(|| async {
+///     Err::<String, _>(warp::reject::custom(HttpApiProblem::new(StatusCode::IM_A_TEAPOT)))
+/// });
+///
+/// let routes = negotiate_warp_rejections(hello);
+/// ```
+pub fn negotiate_warp_rejections<F, R>(
+    filter: F,
+) -> impl Filter<Extract = (warp::reply::Response,), Error = Infallible> + Clone
+where
+    F: Filter<Extract = (R,), Error = Rejection> + Clone + Send + Sync + 'static,
+    R: Reply,
+{
+    let accept = warp::header::optional::<String>("accept")
+        .or(warp::any().map(|| None))
+        .unify();
+
+    let replies = filter
+        .map(|reply: R| Ok(reply.into_response()))
+        .or_else(|rejection| async move { Ok::<_, Infallible>((Err(rejection),)) });
+
+    accept.and(replies).map(
+        |accept: Option<String>, reply: Result<warp::reply::Response, Rejection>| match reply {
+            Ok(response) => response,
+            Err(rejection) => negotiated_response(
+                &HttpApiProblem::from_warp_rejection(&rejection),
+                accept.as_deref().unwrap_or_default(),
+            ),
+        },
+    )
+}
+
+/// Creates a response in the format negotiated with `accept`
+fn negotiated_response(problem: &HttpApiProblem, accept: &str) -> warp::reply::Response {
+    use warp::http::header::{HeaderValue, CONTENT_TYPE};
+    use warp::http::StatusCode;
+
+    let format = ProblemFormat::negotiate(accept);
+
+    let mut response = warp::reply::Response::new(problem.format_bytes(format).into());
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    *response.status_mut() = StatusCode::from_u16(problem.status_code_or_internal_server_error())
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+    response
+}
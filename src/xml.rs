@@ -0,0 +1,386 @@
+//! The XML format of problems (`application/problem+xml`).
+//!
+//! Problems are serialized as described in
+//! [Appendix B of RFC 9457](https://www.rfc-editor.org/rfc/rfc9457#appendix-B):
+//! The root element is a `problem` in the `urn:ietf:rfc:7807` namespace and
+//! every member becomes a child element. Items of arrays become `i` elements
+//! and members of objects become child elements. `null`, empty arrays and
+//! empty objects become empty elements.
+//!
+//! Extension members whose names are not valid XML names are left out.
+//! Since XML has no types, parsing guesses them from the text: the string
+//! `"true"` or `"30"` of an extension member becomes a boolean or a number.
+//!
+//! Requires the `xml` feature
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::name::ResolveResult;
+use quick_xml::NsReader;
+use serde_json::{Map, Number};
+
+use super::*;
+
+/// The namespace of the problem elements
+pub static PROBLEM_XML_NAMESPACE: &str = "urn:ietf:rfc:7807";
+
+const MEMBERS: [&str; 5] = ["type", "status", "title", "detail", "instance"];
+
+impl HttpApiProblem {
+    /// Serialize to an XML `Vec<u8>`
+    ///
+    /// Requires the `xml` feature
+    pub fn xml_bytes(&self) -> Vec<u8> {
+        self.write_xml(None).into_bytes()
+    }
+
+    /// Serialize to an indented XML `String`
+    ///
+    /// Requires the `xml` feature
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// let p = HttpApiProblem::new(StatusCode::FORBIDDEN)
+    ///     .title("You do not have enough credit.")
+    ///     .value("accounts", &["/account/12345", "/account/67890"]);
+    ///
+    /// assert_eq!(
+    ///     p.xml_string(),
+    ///     r#"<?xml version="1.0" encoding="UTF-8"?>
+    /// <problem xmlns="urn:ietf:rfc:7807">
+    ///   <status>403</status>
+    ///   <title>You do not have enough credit.</title>
+    ///   <accounts>
+    ///     <i>/account/12345</i>
+    ///     <i>/account/67890</i>
+    ///   </accounts>
+    /// </problem>
+    /// "#
+    /// );
+    /// ```
+    pub fn xml_string(&self) -> String {
+        self.write_xml(Some(0))
+    }
+
+    /// Parses a problem from its XML format.
+    ///
+    /// Since XML does not have types, the text of extension members becomes
+    /// a number or boolean if it is a valid JSON number or boolean and a string otherwise.
+    /// Empty elements become `null`.
+    ///
+    /// Requires the `xml` feature
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_api_problem::*;
+    ///
+    /// let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+    /// <problem xmlns="urn:ietf:rfc:7807">
+    ///   <status>403</status>
+    ///   <title>You do not have enough credit.</title>
+    ///   <balance>30</balance>
+    /// </problem>"#;
+    ///
+    /// let p = HttpApiProblem::from_xml(xml).unwrap();
+    ///
+    /// assert_eq!(Some(StatusCode::FORBIDDEN), p.status);
+    /// assert_eq!(Some("You do not have enough credit."), p.title.as_deref());
+    /// assert_eq!(Some(30), p.get_value::<&str, u32>("balance"));
+    /// ```
+    pub fn from_xml<T: AsRef<[u8]>>(xml: T) -> Result<Self, ProblemXmlError> {
+        let members = match read_problem(xml.as_ref())? {
+            Some(members) => members,
+            None => return Err(ProblemXmlError::NotAProblem),
+        };
+
+        let members = members
+            .into_iter()
+            .map(|(name, node)| {
+                let value = match (name.as_str(), node) {
+                    ("status", Node::Leaf(text)) => match text.trim().parse::<u16>() {
+                        Ok(status) => Value::from(status),
+                        Err(_) => Value::String(text),
+                    },
+                    ("type" | "title" | "detail" | "instance", Node::Leaf(text))
+                        if !text.is_empty() =>
+                    {
+                        Value::String(text)
+                    }
+                    (_, node) => node.into_value(),
+                };
+                (name, value)
+            })
+            .collect::<Map<_, _>>();
+
+        serde_json::from_value(Value::Object(members)).map_err(ProblemXmlError::InvalidMember)
+    }
+
+    fn write_xml(&self, indent: Option<usize>) -> String {
+        let members = match serde_json::to_value(self) {
+            Ok(Value::Object(members)) => members,
+            _ => Map::new(),
+        };
+
+        let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        newline(&mut xml, indent);
+        xml.push_str(r#"<problem xmlns=""#);
+        xml.push_str(PROBLEM_XML_NAMESPACE);
+        xml.push_str(r#"">"#);
+        newline(&mut xml, indent);
+
+        let children = indent.map(|level| level + 1);
+        for name in MEMBERS {
+            if let Some(value) = members.get(name) {
+                write_element(&mut xml, name, value, children);
+            }
+        }
+        for (name, value) in members.iter() {
+            if !MEMBERS.contains(&name.as_str()) && is_xml_name(name) {
+                write_element(&mut xml, name, value, children);
+            }
+        }
+
+        xml.push_str("</problem>");
+        newline(&mut xml, indent);
+        xml
+    }
+}
+
+/// An error when parsing a problem from XML
+///
+/// Requires the `xml` feature
+#[derive(Debug)]
+pub enum ProblemXmlError {
+    /// The document is not well-formed XML
+    Xml(quick_xml::Error),
+    /// The root element is not a `problem` in the `urn:ietf:rfc:7807` namespace
+    NotAProblem,
+    /// A member of the problem has an invalid value
+    InvalidMember(serde_json::Error),
+}
+
+impl fmt::Display for ProblemXmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemXmlError::Xml(err) => write!(f, "invalid XML: {}", err),
+            ProblemXmlError::NotAProblem => write!(
+                f,
+                "the root element is not a 'problem' in the namespace '{}'",
+                PROBLEM_XML_NAMESPACE
+            ),
+            ProblemXmlError::InvalidMember(err) => write!(f, "invalid problem: {}", err),
+        }
+    }
+}
+
+impl Error for ProblemXmlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProblemXmlError::Xml(err) => Some(err),
+            ProblemXmlError::NotAProblem => None,
+            ProblemXmlError::InvalidMember(err) => Some(err),
+        }
+    }
+}
+
+impl From<quick_xml::Error> for ProblemXmlError {
+    fn from(err: quick_xml::Error) -> Self {
+        ProblemXmlError::Xml(err)
+    }
+}
+
+enum Node {
+    Leaf(String),
+    Branch(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn into_value(self) -> Value {
+        match self {
+            Node::Leaf(text) if text.is_empty() => Value::Null,
+            Node::Leaf(text) => match text.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => match text.parse::<Number>() {
+                    Ok(number) => Value::Number(number),
+                    Err(_) => Value::String(text),
+                },
+            },
+            Node::Branch(children) if children.iter().all(|(name, _)| name == "i") => {
+                Value::Array(children.into_iter().map(|(_, n)| n.into_value()).collect())
+            }
+            Node::Branch(children) => Value::Object(
+                children
+                    .into_iter()
+                    .map(|(name, n)| (name, n.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// An element which is still being read
+struct Open {
+    name: String,
+    text: String,
+    children: Vec<(String, Node)>,
+}
+
+impl Open {
+    fn new(name: &[u8]) -> Self {
+        Open {
+            name: String::from_utf8_lossy(name).into_owned(),
+            text: String::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn close(self) -> (String, Node) {
+        let node = if self.children.is_empty() {
+            Node::Leaf(self.text)
+        } else {
+            Node::Branch(self.children)
+        };
+        (self.name, node)
+    }
+}
+
+/// Reads the members of the root element if it is a problem.
+fn read_problem(xml: &[u8]) -> Result<Option<Vec<(String, Node)>>, ProblemXmlError> {
+    let mut reader = NsReader::from_reader(xml);
+    let mut stack: Vec<Open> = Vec::new();
+
+    loop {
+        match reader.read_resolved_event()? {
+            (namespace, Event::Start(start)) => {
+                if stack.is_empty() && !is_problem(&namespace, start.local_name().as_ref()) {
+                    return Ok(None);
+                }
+                stack.push(Open::new(start.local_name().as_ref()));
+            }
+            (namespace, Event::Empty(start)) => {
+                if stack.is_empty() {
+                    return Ok(is_problem(&namespace, start.local_name().as_ref()).then(Vec::new));
+                }
+                let (name, node) = Open::new(start.local_name().as_ref()).close();
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push((name, node));
+                }
+            }
+            (_, Event::End(_)) => {
+                let (name, node) = match stack.pop() {
+                    Some(open) => open.close(),
+                    None => return Ok(None),
+                };
+                match (stack.last_mut(), node) {
+                    (Some(parent), node) => parent.children.push((name, node)),
+                    (None, Node::Branch(members)) => return Ok(Some(members)),
+                    (None, Node::Leaf(_)) => return Ok(Some(Vec::new())),
+                }
+            }
+            (_, Event::Text(text)) => {
+                if let Some(open) = stack.last_mut() {
+                    open.text.push_str(&text.unescape()?);
+                }
+            }
+            (_, Event::CData(data)) => {
+                if let Some(open) = stack.last_mut() {
+                    open.text.push_str(&String::from_utf8_lossy(&data));
+                }
+            }
+            (_, Event::Eof) => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+fn is_problem(namespace: &ResolveResult, local_name: &[u8]) -> bool {
+    local_name == b"problem"
+        && matches!(namespace, ResolveResult::Bound(ns) if ns.as_ref() == PROBLEM_XML_NAMESPACE.as_bytes())
+}
+
+fn write_element(xml: &mut String, name: &str, value: &Value, indent: Option<usize>) {
+    indentation(xml, indent);
+
+    let text = match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(escape(s).into_owned()),
+        Value::Array(items) if !items.is_empty() => {
+            open_tag(xml, name);
+            newline(xml, indent);
+            for item in items {
+                write_element(xml, "i", item, indent.map(|level| level + 1));
+            }
+            close_tag(xml, name, indent);
+            return;
+        }
+        Value::Object(members) if members.keys().any(|name| is_xml_name(name)) => {
+            open_tag(xml, name);
+            newline(xml, indent);
+            for (name, value) in members.iter().filter(|(name, _)| is_xml_name(name)) {
+                write_element(xml, name, value, indent.map(|level| level + 1));
+            }
+            close_tag(xml, name, indent);
+            return;
+        }
+        Value::Array(_) | Value::Object(_) => None,
+    };
+
+    match text {
+        Some(text) if !text.is_empty() => {
+            open_tag(xml, name);
+            xml.push_str(&text);
+            xml.push_str("</");
+            xml.push_str(name);
+            xml.push('>');
+        }
+        _ => {
+            xml.push('<');
+            xml.push_str(name);
+            xml.push_str("/>");
+        }
+    }
+    newline(xml, indent);
+}
+
+fn open_tag(xml: &mut String, name: &str) {
+    xml.push('<');
+    xml.push_str(name);
+    xml.push('>');
+}
+
+fn close_tag(xml: &mut String, name: &str, indent: Option<usize>) {
+    indentation(xml, indent);
+    xml.push_str("</");
+    xml.push_str(name);
+    xml.push('>');
+    newline(xml, indent);
+}
+
+fn indentation(xml: &mut String, indent: Option<usize>) {
+    if let Some(level) = indent {
+        xml.push_str(&"  ".repeat(level));
+    }
+}
+
+fn newline(xml: &mut String, indent: Option<usize>) {
+    if indent.is_some() {
+        xml.push('\n');
+    }
+}
+
+/// Only a conservative subset of the names allowed by XML
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    !name.get(..3).is_some_and(|p| p.eq_ignore_ascii_case("xml"))
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
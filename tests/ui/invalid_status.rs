@@ -0,0 +1,7 @@
+use http_api_problem::*;
+
+#[derive(IntoApiError)]
+#[api_error(status = 1000)]
+struct Oops;
+
+fn main() {}
@@ -0,0 +1,8 @@
+use http_api_problem::*;
+
+#[derive(IntoApiError)]
+enum StorageError {
+    Corrupted,
+}
+
+fn main() {}
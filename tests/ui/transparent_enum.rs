@@ -0,0 +1,9 @@
+use http_api_problem::*;
+
+#[derive(IntoApiError)]
+#[api_error(transparent)]
+enum HandlerError {
+    Problem(HttpApiProblem),
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use http_api_problem::*;
+
+#[derive(IntoApiError)]
+#[api_error(transparent)]
+struct Wrapper(#[api_error(source)] HttpApiProblem);
+
+fn main() {}
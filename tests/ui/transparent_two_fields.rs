@@ -0,0 +1,9 @@
+use http_api_problem::*;
+
+#[derive(IntoApiError)]
+enum HandlerError {
+    #[api_error(transparent)]
+    Problem(HttpApiProblem, u32),
+}
+
+fn main() {}
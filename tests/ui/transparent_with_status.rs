@@ -0,0 +1,9 @@
+use http_api_problem::*;
+
+#[derive(IntoApiError)]
+enum HandlerError {
+    #[api_error(transparent, status = 500)]
+    Problem(HttpApiProblem),
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use http_api_problem::*;
+
+#[derive(IntoApiError)]
+#[api_error(status = 500)]
+struct Failed {
+    #[api_error(source)]
+    first: std::io::Error,
+    #[api_error(source)]
+    second: std::io::Error,
+}
+
+fn main() {}
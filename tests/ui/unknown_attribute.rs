@@ -0,0 +1,7 @@
+use http_api_problem::*;
+
+#[derive(IntoApiError)]
+#[api_error(status = 500, detail = "oops")]
+struct Oops;
+
+fn main() {}
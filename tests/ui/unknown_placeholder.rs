@@ -0,0 +1,9 @@
+use http_api_problem::*;
+
+#[derive(IntoApiError)]
+#[api_error(status = 404, message = "{id} not found")]
+struct NotFound {
+    key: u32,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use http_api_problem::*;
+
+#[derive(IntoApiError)]
+#[api_error(status = 404)]
+struct NotFound(#[api_error(field)] u32);
+
+fn main() {}